@@ -0,0 +1,169 @@
+use crate::runner;
+use crate::scenarios::Scenario;
+use crate::threads;
+use crate::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::time::Instant;
+
+/// Summary of `Solver::update` latencies, all in seconds.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Stats {
+    pub samples: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub p95: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl Stats {
+    pub fn from_latencies(latencies: &[f32]) -> Option<Stats> {
+        if latencies.is_empty() {
+            return None;
+        }
+        let mut sorted = latencies.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        Some(Stats {
+            samples: sorted.len(),
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            median: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            p99: percentile(&sorted, 0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    /// The metrics compared against a baseline. `max` is left out as a
+    /// single slow frame is mostly noise.
+    fn metrics(&self) -> [(&'static str, f32); 4] {
+        [
+            ("mean", self.mean),
+            ("median", self.median),
+            ("p95", self.p95),
+            ("p99", self.p99),
+        ]
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mean {:.3} ms, median {:.3} ms, p95 {:.3} ms, p99 {:.3} ms, max {:.3} ms ({} samples)",
+            self.mean * 1000.0,
+            self.median * 1000.0,
+            self.p95 * 1000.0,
+            self.p99 * 1000.0,
+            self.max * 1000.0,
+            self.samples
+        )
+    }
+}
+
+/// Nearest-rank percentile of already sorted values.
+fn percentile(sorted: &[f32], fraction: f32) -> f32 {
+    let rank = (fraction * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Steps the scenario `warmup` times without measuring, then measures every
+/// one of its `steps`.
+pub fn run(scenario: &mut Scenario, warmup: usize) -> Stats {
+    for _ in 0..warmup {
+        scenario.solver.update(scenario.dt);
+    }
+    let latencies = runner::run_latencies(scenario);
+    Stats::from_latencies(&latencies).expect("scenario has no steps")
+}
+
+/// Times every step of a fresh scenario, world stepped with swept
+/// collisions, for each of `threads::counts` given to the swept pass. Only
+/// that pass is threaded, so the counts differ by how the swept pass scales
+/// while `Solver::update` takes the same time in each.
+pub fn swept_thread_scaling(scenario: fn() -> Scenario) -> Vec<(usize, Stats)> {
+    threads::counts()
+        .into_iter()
+        .filter_map(|count| {
+            let scenario = scenario();
+            let mut world = World::from_solver(scenario.solver);
+            world.set_swept_collisions(true);
+            world.set_swept_threads(count);
+            let latencies: Vec<f32> = (0..scenario.steps)
+                .map(|_| {
+                    let clock = Instant::now();
+                    world.update(scenario.dt);
+                    clock.elapsed().as_secs_f32()
+                })
+                .collect();
+            Stats::from_latencies(&latencies).map(|stats| (count, stats))
+        })
+        .collect()
+}
+
+/// Stats of earlier runs, keyed by scenario name.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub scenarios: BTreeMap<String, Stats>,
+}
+
+impl Baseline {
+    pub fn load(path: &str) -> std::io::Result<Baseline> {
+        let mut data = String::new();
+        File::open(path)?.read_to_string(&mut data)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        File::create(path)?.write_all(data.as_bytes())
+    }
+}
+
+/// A metric that got slower than the baseline by more than the threshold.
+#[derive(Clone, Debug)]
+pub struct Regression {
+    pub metric: &'static str,
+    pub baseline: f32,
+    pub current: f32,
+}
+
+impl Regression {
+    /// Relative slowdown, 0.1 meaning 10% slower.
+    pub fn change(&self) -> f32 {
+        self.current / self.baseline - 1.0
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:.3} ms -> {:.3} ms (+{:.1}%)",
+            self.metric,
+            self.baseline * 1000.0,
+            self.current * 1000.0,
+            self.change() * 100.0
+        )
+    }
+}
+
+/// Every metric of `current` that is more than `threshold` (0.1 for 10%)
+/// slower than `baseline`.
+pub fn compare(baseline: &Stats, current: &Stats, threshold: f32) -> Vec<Regression> {
+    baseline
+        .metrics()
+        .iter()
+        .zip(current.metrics().iter())
+        .filter(|((_, before), (_, after))| *before > 0.0 && *after > *before * (1.0 + threshold))
+        .map(|((metric, before), (_, after))| Regression {
+            metric: *metric,
+            baseline: *before,
+            current: *after,
+        })
+        .collect()
+}
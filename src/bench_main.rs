@@ -0,0 +1,98 @@
+use bendy2d_testing::bench::{self, Baseline};
+use bendy2d_testing::scenarios;
+
+/// Benchmarks `Solver::update` on the performance scenarios and compares the
+/// results against a stored baseline.
+///
+/// Usage: `bench [--warmup <steps>] [--baseline <path>] [--threshold <fraction>] [--save] [scenario...]`
+fn main() {
+    let mut warmup = 100;
+    let mut baseline_path = "bench/baseline.json".to_string();
+    let mut threshold = 0.1;
+    let mut save = false;
+    let mut names = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--warmup" => warmup = parse_arg(&arg, args.next()),
+            "--baseline" => baseline_path = parse_arg(&arg, args.next()),
+            "--threshold" => threshold = parse_arg(&arg, args.next()),
+            "--save" => save = true,
+            _ => names.push(arg),
+        }
+    }
+    if names.is_empty() {
+        names = vec![
+            "performance1".to_string(),
+            "performance2".to_string(),
+            "performance3".to_string(),
+        ];
+    }
+
+    let mut baseline = match Baseline::load(&baseline_path) {
+        Ok(baseline) => Some(baseline),
+        Err(e) if save => {
+            println!("Starting a new baseline: {}", e);
+            None
+        }
+        Err(e) => {
+            println!("No baseline to compare against: {}", e);
+            None
+        }
+    };
+
+    let mut regression_count = 0;
+    let mut results = Baseline::default();
+    for name in names.iter() {
+        let mut scenario = match scenarios::by_name(name) {
+            Some(scenario) => scenario,
+            None => {
+                println!("Unknown scenario: {}", name);
+                std::process::exit(2);
+            }
+        };
+        let stats = bench::run(&mut scenario, warmup);
+        println!("{}: {}", name, stats);
+
+        if let Some(previous) = baseline.as_ref().and_then(|b| b.scenarios.get(name)) {
+            for regression in bench::compare(previous, &stats, threshold) {
+                println!("  regression: {}", regression);
+                regression_count += 1;
+            }
+        }
+        results.scenarios.insert(name.clone(), stats);
+    }
+
+    if save {
+        // Keep scenarios that were not run this time
+        let mut merged = baseline.take().unwrap_or_default();
+        merged.scenarios.append(&mut results.scenarios);
+        if let Some(dir) = std::path::Path::new(&baseline_path).parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match merged.save(&baseline_path) {
+            Ok(()) => println!("Saved baseline to {}", baseline_path),
+            Err(e) => println!("Error saving baseline: {}", e),
+        }
+    }
+
+    if regression_count > 0 {
+        println!(
+            "{} metrics regressed by more than {}%!",
+            regression_count,
+            threshold * 100.0
+        );
+        std::process::exit(1);
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(name: &str, value: Option<String>) -> T {
+    match value.as_deref().map(str::parse::<T>) {
+        Some(Ok(value)) => value,
+        _ => {
+            println!("Missing or invalid value for {}", name);
+            std::process::exit(2);
+        }
+    }
+}
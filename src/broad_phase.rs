@@ -0,0 +1,255 @@
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Instant;
+
+/// Smallest grid cell, so boxes of free particles, which have no size,
+/// don't shrink the cells towards nothing.
+pub const MIN_CELL_SIZE: f32 = 1.0;
+
+/// Most grid cells one box is put in. Boxes covering more, like a huge body
+/// or one far enough out that its cells can't be counted, are tested
+/// against every other box instead.
+pub const MAX_CELLS_PER_BOX: i64 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+}
+
+impl Aabb {
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vector2<f32>>) -> Self {
+        let mut aabb = Aabb {
+            min: Vector2::repeat(f32::INFINITY),
+            max: Vector2::repeat(f32::NEG_INFINITY),
+        };
+        for point in points {
+            aabb.min = aabb.min.inf(point);
+            aabb.max = aabb.max.sup(point);
+        }
+        aabb
+    }
+
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+}
+
+/// How candidate pairs are found before the exact per-edge tests of the
+/// passes `World` runs itself: swept collisions, the contact search and the
+/// penetration check. Every strategy returns the same pairs, only the cost
+/// differs.
+///
+/// This is not the broad phase of `Solver::update`. The solver's collision
+/// step lives in `bendy2d` and can't be given a pair list, so it tests
+/// every pair whatever is chosen here and choosing one does not change its
+/// collision work. See [`PairDiagnostics`] for what it would save.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadPhase {
+    /// Tests every pair of boxes.
+    BruteForce,
+    /// Buckets boxes into a uniform grid with cells twice the mean size of
+    /// the boxes that have one, and at least `MIN_CELL_SIZE`. Boxes over
+    /// `MAX_CELLS_PER_BOX` cells are tested against every box instead.
+    Grid,
+    /// Sorts boxes along x and only tests the ones whose x ranges overlap.
+    #[default]
+    SweepAndPrune,
+}
+
+impl BroadPhase {
+    pub const ALL: [BroadPhase; 3] = [
+        BroadPhase::BruteForce,
+        BroadPhase::Grid,
+        BroadPhase::SweepAndPrune,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BroadPhase::BruteForce => "Brute force",
+            BroadPhase::Grid => "Grid",
+            BroadPhase::SweepAndPrune => "Sweep and prune",
+        }
+    }
+
+    pub fn increase(&mut self) {
+        *self = match self {
+            BroadPhase::BruteForce => BroadPhase::Grid,
+            BroadPhase::Grid => BroadPhase::SweepAndPrune,
+            BroadPhase::SweepAndPrune => BroadPhase::BruteForce,
+        }
+    }
+
+    /// Index pairs `(a, b)` with `a < b` whose boxes overlap, sorted.
+    pub fn pairs(&self, aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+        let mut pairs = match self {
+            BroadPhase::BruteForce => brute_force(aabbs),
+            BroadPhase::Grid => grid(aabbs),
+            BroadPhase::SweepAndPrune => sweep_and_prune(aabbs),
+        };
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+fn brute_force(aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for a in 0..aabbs.len() {
+        for b in a + 1..aabbs.len() {
+            if aabbs[a].overlaps(&aabbs[b]) {
+                pairs.push((a, b));
+            }
+        }
+    }
+    pairs
+}
+
+fn grid(aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+    if aabbs.is_empty() {
+        return Vec::new();
+    }
+    let sizes: Vec<f32> = aabbs
+        .iter()
+        .map(|aabb| (aabb.max - aabb.min).max())
+        .filter(|size| *size > 0.0 && size.is_finite())
+        .collect();
+    let mean_size = sizes.iter().sum::<f32>() / sizes.len().max(1) as f32;
+    let cell_size = (mean_size * 2.0).max(MIN_CELL_SIZE);
+    // Casts saturate, so boxes out at infinity get a huge cell count
+    let cell = |pos: Vector2<f32>| {
+        (
+            (pos.x / cell_size).floor() as i64,
+            (pos.y / cell_size).floor() as i64,
+        )
+    };
+    let span = |min: i64, max: i64| max.saturating_sub(min).saturating_add(1);
+
+    let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    let mut oversized = Vec::new();
+    for (index, aabb) in aabbs.iter().enumerate() {
+        let (min_x, min_y) = cell(aabb.min);
+        let (max_x, max_y) = cell(aabb.max);
+        let count = span(min_x, max_x).saturating_mul(span(min_y, max_y));
+        if !(1..=MAX_CELLS_PER_BOX).contains(&count) {
+            oversized.push(index);
+            continue;
+        }
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for indices in cells.values() {
+        for (i, a) in indices.iter().enumerate() {
+            for b in indices[i + 1..].iter() {
+                if aabbs[*a].overlaps(&aabbs[*b]) {
+                    pairs.push((*a.min(b), *a.max(b)));
+                }
+            }
+        }
+    }
+    for a in oversized {
+        for b in 0..aabbs.len() {
+            if a != b && aabbs[a].overlaps(&aabbs[b]) {
+                pairs.push((a.min(b), a.max(b)));
+            }
+        }
+    }
+    // Boxes sharing several cells are found once per cell, and two
+    // oversized boxes once from each side
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+fn sweep_and_prune(aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..aabbs.len()).collect();
+    order.sort_by(|a, b| aabbs[*a].min.x.total_cmp(&aabbs[*b].min.x));
+
+    let mut pairs = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+    for index in order {
+        let aabb = &aabbs[index];
+        active.retain(|other| aabbs[*other].max.x >= aabb.min.x);
+        for other in active.iter() {
+            if aabb.overlaps(&aabbs[*other]) {
+                pairs.push((index.min(*other), index.max(*other)));
+            }
+        }
+        active.push(index);
+    }
+    pairs
+}
+
+/// The boxes kept in `polygon.bounds`, one per polygon.
+pub fn polygon_aabbs(solver: &Solver) -> Vec<Aabb> {
+    solver
+        .get_polygons()
+        .iter()
+        .map(|polygon| Aabb {
+            min: polygon.bounds.pos,
+            max: polygon.bounds.pos + polygon.bounds.size,
+        })
+        .collect()
+}
+
+/// Polygon pairs before and after culling, with how long each strategy took
+/// to find them. A diagnostic only: it shows how much a broad phase would
+/// save, but the solver's collision step doesn't use it.
+pub struct PairDiagnostics {
+    pub polygons: usize,
+    pub overlapping: usize,
+    pub timings: Vec<(BroadPhase, f32)>,
+}
+
+impl PairDiagnostics {
+    pub fn all(&self) -> usize {
+        self.polygons * self.polygons.saturating_sub(1) / 2
+    }
+}
+
+impl fmt::Display for PairDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "polygon pairs: {} before culling, {} after (diagnostic, the solver still tests every pair)",
+            self.all(),
+            self.overlapping
+        )?;
+        for (broad_phase, seconds) in self.timings.iter() {
+            write!(
+                f,
+                "\n  {:<16} {:>10.1}us",
+                broad_phase.name(),
+                seconds * 1e6
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn pair_diagnostics(solver: &Solver) -> PairDiagnostics {
+    let aabbs = polygon_aabbs(solver);
+    let mut overlapping = 0;
+    let mut timings = Vec::new();
+    for broad_phase in BroadPhase::ALL {
+        let clock = Instant::now();
+        overlapping = broad_phase.pairs(&aabbs).len();
+        timings.push((broad_phase, clock.elapsed().as_secs_f32()));
+    }
+    PairDiagnostics {
+        polygons: aabbs.len(),
+        overlapping,
+        timings,
+    }
+}
@@ -0,0 +1,378 @@
+use crate::broad_phase::{Aabb, BroadPhase};
+use crate::filter::{FilterTable, SolverFilter};
+use crate::pick::BodyRef;
+use crate::scenarios::Scenario;
+use crate::threads::Threads;
+use bendy2d::common::is_point_in_polygon;
+use bendy2d::particle::Particle;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use std::fmt;
+
+/// How far in front of an edge a point is put back after a swept hit, so the
+/// next step's narrow phase sees it on the side it came from.
+const SKIN: f32 = 1e-3;
+
+/// Steps a penetration may last before `check_swept` counts it as stuck.
+pub const MAX_PENETRATION_STEPS: usize = 10;
+
+/// A polygon particle that ended a step inside another polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Penetration {
+    pub polygon: usize,
+    pub particle: usize,
+    pub other: usize,
+}
+
+pub trait SolverCcd {
+    /// `Solver::update`, then sweeps every polygon and free particle from
+    /// where it started the step to where it ended up. A point whose path
+    /// crossed a polygon edge or static line is put back on the edge and
+    /// loses the part of its velocity that carried it through. Returns how
+    /// many points were corrected. Only bodies whose swept boxes overlap
+    /// in `broad_phase` and collide under `filters` are tested against each
+    /// other, and the sweeps are spread over `threads`.
+    fn update_swept(
+        &mut self,
+        dt: f32,
+        broad_phase: BroadPhase,
+        threads: &Threads,
+        filters: &FilterTable,
+    ) -> usize;
+}
+
+/// A polygon edge or static line where it was at the start and end of a
+/// step.
+pub struct Edge {
+    pub start: [Vector2<f32>; 2],
+    pub end: [Vector2<f32>; 2],
+    /// The polygon the edge belongs to, None for a static line.
+    pub polygon: Option<usize>,
+}
+
+/// Where a swept point is put back, and the edge it was put back on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub pos: Vector2<f32>,
+    pub normal: Vector2<f32>,
+    pub edge_velocity: Vector2<f32>,
+}
+
+fn polygon_points(solver: &Solver) -> Vec<Vec<Vector2<f32>>> {
+    solver
+        .get_polygons()
+        .iter()
+        .map(|polygon| polygon.particles.iter().map(|p| p.pos).collect())
+        .collect()
+}
+
+/// Unit normal of the edge `a` → `b`, None if the edge has collapsed.
+fn normal(a: Vector2<f32>, b: Vector2<f32>) -> Option<Vector2<f32>> {
+    let edge = b - a;
+    Vector2::new(-edge.y, edge.x).try_normalize(f32::EPSILON)
+}
+
+/// Earliest edge crossed by a point moving `from` → `to`, with each edge
+/// moving by the average of its end points' motion. Edges of polygons the
+/// point already started inside are left to the narrow phase, and so is an
+/// edge that collapsed to a point, as it has no side to put the point on.
+/// `polygons` are the polygons' points at the start of the step.
+pub fn sweep<'a>(
+    from: Vector2<f32>,
+    to: Vector2<f32>,
+    edges: impl Iterator<Item = &'a Edge>,
+    polygons: &[Vec<Vector2<f32>>],
+) -> Option<Hit> {
+    let mut earliest: Option<(f32, f32, &Edge)> = None;
+    for edge in edges {
+        if let Some(polygon) = edge.polygon {
+            if is_point_in_polygon(&from, &polygons[polygon]).is_some() {
+                continue;
+            }
+        }
+        if normal(edge.end[0], edge.end[1]).is_none() {
+            continue;
+        }
+        let edge_motion = (edge.end[0] - edge.start[0] + edge.end[1] - edge.start[1]) / 2.0;
+        let motion = to - from - edge_motion;
+        let along = edge.start[1] - edge.start[0];
+        let denominator = motion.perp(&along);
+        if denominator.abs() < f32::EPSILON {
+            continue;
+        }
+        let offset = edge.start[0] - from;
+        let t = offset.perp(&along) / denominator;
+        let u = offset.perp(&motion) / denominator;
+        if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+            continue;
+        }
+        if earliest.map_or(true, |(earliest_t, _, _)| t < earliest_t) {
+            earliest = Some((t, u, edge));
+        }
+    }
+
+    let (_, u, edge) = earliest?;
+    // Push back out on the side the point came from
+    let side = (from - edge.start[0]).dot(&normal(edge.start[0], edge.start[1])?);
+    let mut normal = normal(edge.end[0], edge.end[1])?;
+    if side < 0.0 {
+        normal = -normal;
+    }
+    let on_edge = edge.end[0] + (edge.end[1] - edge.end[0]) * u;
+    Some(Hit {
+        pos: on_edge + normal * SKIN,
+        normal,
+        edge_velocity: (edge.end[0] - edge.start[0] + edge.end[1] - edge.start[1]) / 2.0,
+    })
+}
+
+fn resolve(particle: &mut Particle, hit: &Hit) {
+    let mut velocity = particle.pos - particle.prev_pos;
+    let approach = (velocity - hit.edge_velocity).dot(&hit.normal);
+    if approach < 0.0 {
+        velocity -= hit.normal * approach;
+    }
+    particle.pos = hit.pos;
+    particle.prev_pos = hit.pos - velocity;
+}
+
+impl SolverCcd for Solver {
+    fn update_swept(
+        &mut self,
+        dt: f32,
+        broad_phase: BroadPhase,
+        threads: &Threads,
+        filters: &FilterTable,
+    ) -> usize {
+        let polygons_before = polygon_points(self);
+        let particles_before: Vec<Vector2<f32>> =
+            self.get_particles().iter().map(|p| p.pos).collect();
+        self.update_filtered(dt, filters);
+        let polygons_after = polygon_points(self);
+        let particles_after: Vec<Vector2<f32>> =
+            self.get_particles().iter().map(|p| p.pos).collect();
+
+        // Bodies with edges come first, polygons then static lines, followed
+        // by the free particles
+        let mut owners: Vec<Vec<Edge>> = Vec::new();
+        let mut aabbs = Vec::new();
+        for (p, (before, after)) in polygons_before
+            .iter()
+            .zip(polygons_after.iter())
+            .enumerate()
+        {
+            let edges = (0..before.len())
+                .map(|i| {
+                    let j = (i + 1) % before.len();
+                    Edge {
+                        start: [before[i], before[j]],
+                        end: [after[i], after[j]],
+                        polygon: Some(p),
+                    }
+                })
+                .collect();
+            owners.push(edges);
+            aabbs.push(Aabb::from_points(before.iter().chain(after.iter())));
+        }
+        for line in self.get_static_lines().iter() {
+            owners.push(vec![Edge {
+                start: [line.0, line.1],
+                end: [line.0, line.1],
+                polygon: None,
+            }]);
+            aabbs.push(Aabb::from_points([&line.0, &line.1]));
+        }
+        for (from, to) in particles_before.iter().zip(particles_after.iter()) {
+            aabbs.push(Aabb::from_points([from, to]));
+        }
+
+        let polygon_count = polygons_before.len();
+        let owner_count = owners.len();
+        let moves = |body: usize| body < polygon_count || body >= owner_count;
+        let body_ref = |body: usize| match body {
+            _ if body < polygon_count => BodyRef::Polygon { index: body },
+            _ if body < owner_count => BodyRef::StaticLine {
+                index: body - polygon_count,
+            },
+            _ => BodyRef::Particle {
+                index: body - owner_count,
+            },
+        };
+        let mut nearby: Vec<Vec<usize>> = vec![Vec::new(); aabbs.len()];
+        for (a, b) in broad_phase.pairs(&aabbs) {
+            if !filters.collides(body_ref(a), body_ref(b)) {
+                continue;
+            }
+            if moves(a) && b < owner_count {
+                nearby[a].push(b);
+            }
+            if moves(b) && a < owner_count {
+                nearby[b].push(a);
+            }
+        }
+
+        // Every moving point with the body it belongs to, polygon points
+        // first and then the free particles
+        let mut movers: Vec<(usize, Vector2<f32>, Vector2<f32>)> = Vec::new();
+        for (p, (before, after)) in polygons_before
+            .iter()
+            .zip(polygons_after.iter())
+            .enumerate()
+        {
+            movers.extend(
+                before
+                    .iter()
+                    .zip(after.iter())
+                    .map(|(from, to)| (p, *from, *to)),
+            );
+        }
+        for (i, (from, to)) in particles_before
+            .iter()
+            .zip(particles_after.iter())
+            .enumerate()
+        {
+            movers.push((owner_count + i, *from, *to));
+        }
+        let hits = threads.map(&movers, |(body, from, to)| {
+            let edges = nearby[*body].iter().flat_map(|owner| owners[*owner].iter());
+            sweep(*from, *to, edges, &polygons_before)
+        });
+
+        // Corrections are applied in mover order whatever the thread count
+        let mut corrected = 0;
+        let mut hits = hits.into_iter();
+        for (p, points) in polygons_before.iter().enumerate() {
+            for (i, hit) in hits.by_ref().take(points.len()).enumerate() {
+                let Some(hit) = hit else {
+                    continue;
+                };
+                if let Some(particle) = self
+                    .get_polygon_mut(p)
+                    .and_then(|polygon| polygon.particles.get_mut(i))
+                {
+                    resolve(particle, &hit);
+                    corrected += 1;
+                }
+            }
+        }
+        for (i, hit) in hits.enumerate() {
+            let Some(hit) = hit else {
+                continue;
+            };
+            if let Some(particle) = self.get_particle_mut(i) {
+                resolve(particle, &hit);
+                corrected += 1;
+            }
+        }
+        corrected
+    }
+}
+
+/// Every polygon particle lying inside a polygon other than its own.
+pub fn penetrations(solver: &Solver) -> Vec<Penetration> {
+    let polygons = polygon_points(solver);
+    let aabbs: Vec<Aabb> = polygons.iter().map(Aabb::from_points).collect();
+    let mut penetrations = Vec::new();
+    for (a, b) in BroadPhase::default().pairs(&aabbs) {
+        for (polygon, other) in [(a, b), (b, a)] {
+            for (particle, pos) in polygons[polygon].iter().enumerate() {
+                if is_point_in_polygon(pos, &polygons[other]).is_some() {
+                    penetrations.push(Penetration {
+                        polygon,
+                        particle,
+                        other,
+                    });
+                }
+            }
+        }
+    }
+    penetrations
+}
+
+#[derive(Clone, Debug)]
+pub enum CcdFailure {
+    /// Two polygons ended up on the opposite sides of each other from where
+    /// they started.
+    Tunnelled {
+        frame: usize,
+        polygon: usize,
+        other: usize,
+    },
+    /// A penetration lasted longer than `MAX_PENETRATION_STEPS`, or was still
+    /// there when the run ended.
+    Stuck {
+        frame: usize,
+        penetration: Penetration,
+    },
+}
+
+impl fmt::Display for CcdFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CcdFailure::Tunnelled {
+                frame,
+                polygon,
+                other,
+            } => write!(
+                f,
+                "frame {}: polygon {} passed through polygon {}",
+                frame, polygon, other
+            ),
+            CcdFailure::Stuck { frame, penetration } => write!(
+                f,
+                "frame {}: particle {} of polygon {} is stuck inside polygon {}",
+                frame, penetration.particle, penetration.polygon, penetration.other
+            ),
+        }
+    }
+}
+
+/// Runs a scenario with swept collisions and checks that no polygon passes
+/// through another and no penetration survives. Polygons are expected to
+/// meet head on, as in `high_speeds`, so any pair whose centers swap sides
+/// along the line between them counts as tunnelling.
+pub fn check_swept(scenario: &mut Scenario) -> Result<(), CcdFailure> {
+    let centers = |solver: &Solver| -> Vec<Vector2<f32>> {
+        solver.get_polygons().iter().map(|p| p.center).collect()
+    };
+    let start = centers(&scenario.solver);
+    let mut stuck_steps = 0;
+    for frame in 0..scenario.steps {
+        scenario.solver.update_swept(
+            scenario.dt,
+            BroadPhase::default(),
+            &Threads::default(),
+            &FilterTable::default(),
+        );
+
+        let current = centers(&scenario.solver);
+        for polygon in 0..start.len() {
+            for other in polygon + 1..start.len() {
+                let apart = start[other] - start[polygon];
+                if (current[other] - current[polygon]).dot(&apart) < 0.0 {
+                    return Err(CcdFailure::Tunnelled {
+                        frame,
+                        polygon,
+                        other,
+                    });
+                }
+            }
+        }
+
+        let penetrations = penetrations(&scenario.solver);
+        stuck_steps = if penetrations.is_empty() {
+            0
+        } else {
+            stuck_steps + 1
+        };
+        if stuck_steps > MAX_PENETRATION_STEPS || frame + 1 == scenario.steps {
+            if let Some(penetration) = penetrations.first() {
+                return Err(CcdFailure::Stuck {
+                    frame,
+                    penetration: *penetration,
+                });
+            }
+        }
+    }
+    Ok(())
+}
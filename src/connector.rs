@@ -0,0 +1,127 @@
+use crate::pick::{point_particle, point_particle_mut, PointRef};
+use crate::world::{BodyId, CircleId, ParticleId, PolygonId};
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// One end of a connector.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Endpoint {
+    Particle {
+        particle: ParticleId,
+    },
+    /// The center of a circle.
+    Circle {
+        circle: CircleId,
+    },
+    PolygonVertex {
+        polygon: PolygonId,
+        vertex: usize,
+    },
+    /// A fixed point in the world.
+    World {
+        pos: Vector2<f32>,
+    },
+}
+
+impl Endpoint {
+    /// The body the endpoint is on, None for the world.
+    pub fn body(&self) -> Option<BodyId> {
+        match *self {
+            Endpoint::Particle { particle } => Some(BodyId::Particle(particle)),
+            Endpoint::Circle { circle } => Some(BodyId::Circle(circle)),
+            Endpoint::PolygonVertex { polygon, .. } => Some(BodyId::Polygon(polygon)),
+            Endpoint::World { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectorKind {
+    /// Pulls the ends towards `rest_length` apart, harder the further off
+    /// they are. `stiffness` is the acceleration per unit of stretch.
+    Spring { rest_length: f32, stiffness: f32 },
+    /// Keeps the ends exactly `target_distance` apart.
+    Link { target_distance: f32 },
+}
+
+/// A spring or link whose ends can be any kind of point, unlike
+/// `bendy2d::spring::Spring` and `bendy2d::link::Link` which only join two
+/// free particles or two circles.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Connector {
+    pub a: Endpoint,
+    pub b: Endpoint,
+    pub kind: ConnectorKind,
+}
+
+/// Where an endpoint is in the solver right now.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolved {
+    Point(PointRef),
+    Fixed(Vector2<f32>),
+}
+
+impl Resolved {
+    pub fn pos(&self, solver: &Solver) -> Option<Vector2<f32>> {
+        match self {
+            Resolved::Point(point) => point_particle(solver, *point).map(|p| p.pos),
+            Resolved::Fixed(pos) => Some(*pos),
+        }
+    }
+
+    fn moves(&self) -> bool {
+        matches!(self, Resolved::Point(_))
+    }
+}
+
+fn shift(solver: &mut Solver, end: Resolved, offset: Vector2<f32>) {
+    if let Resolved::Point(point) = end {
+        if let Some(particle) = point_particle_mut(solver, point) {
+            particle.pos += offset;
+        }
+    }
+}
+
+/// Applies every connector after a solver update of `dt` seconds. `ends`
+/// holds where the ends of each connector are, None for a connector with a
+/// missing end.
+pub fn solve(
+    solver: &mut Solver,
+    connectors: &[Connector],
+    ends: &[Option<(Resolved, Resolved)>],
+    dt: f32,
+) {
+    for (connector, ends) in connectors.iter().zip(ends.iter()) {
+        let Some((a, b)) = *ends else {
+            continue;
+        };
+        let (Some(pos_a), Some(pos_b)) = (a.pos(solver), b.pos(solver)) else {
+            continue;
+        };
+        // Share of the correction taken by each end, nothing for the world
+        let (weight_a, weight_b) = match (a.moves(), b.moves()) {
+            (true, true) => (0.5, 0.5),
+            (true, false) => (1.0, 0.0),
+            (false, true) => (0.0, 1.0),
+            (false, false) => continue,
+        };
+
+        let between = pos_b - pos_a;
+        let distance = between.magnitude();
+        let Some(direction) = between.try_normalize(f32::EPSILON) else {
+            continue;
+        };
+        let correction = match connector.kind {
+            ConnectorKind::Spring {
+                rest_length,
+                stiffness,
+            } => (distance - rest_length) * (stiffness * dt * dt).min(1.0),
+            ConnectorKind::Link { target_distance } => distance - target_distance,
+        };
+        shift(solver, a, direction * correction * weight_a);
+        shift(solver, b, -direction * correction * weight_b);
+    }
+}
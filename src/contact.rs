@@ -0,0 +1,317 @@
+use crate::broad_phase::{Aabb, BroadPhase};
+use crate::filter::FilterTable;
+use crate::pick::{BodyRef, PointRef};
+use bendy2d::common::is_point_in_polygon;
+use bendy2d::particle::Particle;
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+
+/// How far from a surface a point still counts as touching it.
+pub const SLOP: f32 = 1e-2;
+
+/// A point resting on or pushed into the surface of another body.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    /// The touching point. Circles touch with their edge.
+    pub point: PointRef,
+    /// The polygon, circle or static line being touched.
+    pub other: BodyRef,
+    /// Nearest point on the surface of `other`.
+    pub pos: Vector2<f32>,
+    /// Unit direction out of `other` towards `point`.
+    pub normal: Vector2<f32>,
+    /// How far the point is inside `other`, negative while it is still up
+    /// to `SLOP` away.
+    pub depth: f32,
+    /// Distance the surface of `other` moved at `pos` during the last step.
+    pub surface_velocity: Vector2<f32>,
+}
+
+impl Contact {
+    /// How fast the point moved along `normal` relative to the surface
+    /// during the last step, per step.
+    pub fn normal_speed(&self, particle: &Particle) -> f32 {
+        (velocity(particle) - self.surface_velocity).dot(&self.normal)
+    }
+
+    /// The same speed as it would have been without the contact, from the
+    /// point's velocity `before` the step and the step's pull of gravity.
+    pub fn expected_speed(&self, before: Vector2<f32>, gravity_step: Vector2<f32>) -> f32 {
+        (before + gravity_step - self.surface_velocity).dot(&self.normal)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Body {
+    Polygon(usize),
+    Particle(usize),
+    Circle(usize),
+    StaticLine(usize),
+}
+
+fn velocity(particle: &Particle) -> Vector2<f32> {
+    particle.pos - particle.prev_pos
+}
+
+/// Nearest point to `pos` on the segment `a` → `b`, and how far along it is.
+pub fn closest_on_segment(
+    pos: Vector2<f32>,
+    a: Vector2<f32>,
+    b: Vector2<f32>,
+) -> (Vector2<f32>, f32) {
+    let line = b - a;
+    let length_squared = line.magnitude_squared();
+    if length_squared == 0.0 {
+        return (a, 0.0);
+    }
+    let t = ((pos - a).dot(&line) / length_squared).clamp(0.0, 1.0);
+    (a + line * t, t)
+}
+
+/// Direction from `from` to `to`, or `fallback` when they coincide.
+fn direction(from: Vector2<f32>, to: Vector2<f32>, fallback: Vector2<f32>) -> Vector2<f32> {
+    (to - from).try_normalize(f32::EPSILON).unwrap_or(fallback)
+}
+
+fn against_polygon(
+    point: PointRef,
+    pos: Vector2<f32>,
+    radius: f32,
+    index: usize,
+    polygon: &Polygon,
+) -> Option<Contact> {
+    let particles = &polygon.particles;
+    if particles.len() < 2 {
+        return None;
+    }
+    let mut nearest: Option<(f32, Vector2<f32>, f32, usize)> = None;
+    for i in 0..particles.len() {
+        let j = (i + 1) % particles.len();
+        let (closest, t) = closest_on_segment(pos, particles[i].pos, particles[j].pos);
+        let distance = (pos - closest).magnitude();
+        if nearest.map_or(true, |(nearest_distance, ..)| distance < nearest_distance) {
+            nearest = Some((distance, closest, t, i));
+        }
+    }
+    let (distance, closest, t, i) = nearest?;
+    let j = (i + 1) % particles.len();
+
+    let outwards = direction(polygon.center, closest, Vector2::y());
+    let points: Vec<Vector2<f32>> = particles.iter().map(|p| p.pos).collect();
+    let (normal, depth) = if is_point_in_polygon(&pos, &points).is_some() {
+        (direction(pos, closest, outwards), distance + radius)
+    } else if distance <= radius + SLOP {
+        (direction(closest, pos, outwards), radius - distance)
+    } else {
+        return None;
+    };
+    Some(Contact {
+        point,
+        other: BodyRef::Polygon { index },
+        pos: closest,
+        normal,
+        depth,
+        surface_velocity: velocity(&particles[i]) * (1.0 - t) + velocity(&particles[j]) * t,
+    })
+}
+
+fn against_line(
+    point: PointRef,
+    pos: Vector2<f32>,
+    radius: f32,
+    index: usize,
+    line: (Vector2<f32>, Vector2<f32>),
+) -> Option<Contact> {
+    let (closest, _) = closest_on_segment(pos, line.0, line.1);
+    let distance = (pos - closest).magnitude();
+    if distance > radius + SLOP {
+        return None;
+    }
+    let along = line.1 - line.0;
+    let across = Vector2::new(-along.y, along.x)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vector2::y());
+    Some(Contact {
+        point,
+        other: BodyRef::StaticLine { index },
+        pos: closest,
+        normal: direction(closest, pos, across),
+        depth: radius - distance,
+        surface_velocity: Vector2::zeros(),
+    })
+}
+
+fn against_circle(
+    point: PointRef,
+    pos: Vector2<f32>,
+    radius: f32,
+    index: usize,
+    circle_pos: Vector2<f32>,
+    circle_radius: f32,
+    circle_velocity: Vector2<f32>,
+) -> Option<Contact> {
+    let distance = (pos - circle_pos).magnitude();
+    if distance > radius + circle_radius + SLOP {
+        return None;
+    }
+    let normal = direction(circle_pos, pos, Vector2::y());
+    Some(Contact {
+        point,
+        other: BodyRef::Circle { index },
+        pos: circle_pos + normal * circle_radius,
+        normal,
+        depth: radius + circle_radius - distance,
+        surface_velocity: circle_velocity,
+    })
+}
+
+impl Body {
+    fn body_ref(&self) -> BodyRef {
+        match *self {
+            Body::Polygon(index) => BodyRef::Polygon { index },
+            Body::Particle(index) => BodyRef::Particle { index },
+            Body::Circle(index) => BodyRef::Circle { index },
+            Body::StaticLine(index) => BodyRef::StaticLine { index },
+        }
+    }
+}
+
+/// Every point touching a polygon, circle or static line, found among the
+/// pairs `broad_phase` keeps that collide under `filters`. Points of a
+/// polygon never touch their own polygon, and each pair of circles gives
+/// one contact, from the lower index.
+pub fn find_contacts(
+    solver: &Solver,
+    broad_phase: BroadPhase,
+    filters: &FilterTable,
+) -> Vec<Contact> {
+    let polygons = solver.get_polygons();
+    let particles = solver.get_particles();
+    let circles = solver.get_circles();
+    let lines = solver.get_static_lines();
+    let grow = |aabb: Aabb, by: f32| Aabb {
+        min: aabb.min - Vector2::repeat(by),
+        max: aabb.max + Vector2::repeat(by),
+    };
+
+    let mut bodies = Vec::new();
+    let mut aabbs = Vec::new();
+    for (index, polygon) in polygons.iter().enumerate() {
+        bodies.push(Body::Polygon(index));
+        aabbs.push(grow(
+            Aabb::from_points(polygon.particles.iter().map(|p| &p.pos)),
+            SLOP,
+        ));
+    }
+    for (index, particle) in particles.iter().enumerate() {
+        bodies.push(Body::Particle(index));
+        aabbs.push(grow(Aabb::from_points([&particle.pos]), SLOP));
+    }
+    for (index, circle) in circles.iter().enumerate() {
+        bodies.push(Body::Circle(index));
+        aabbs.push(grow(
+            Aabb::from_points([&circle.point.pos]),
+            circle.radius + SLOP,
+        ));
+    }
+    for (index, line) in lines.iter().enumerate() {
+        bodies.push(Body::StaticLine(index));
+        aabbs.push(grow(Aabb::from_points([&line.0, &line.1]), SLOP));
+    }
+
+    let vertices = |polygon: usize| {
+        polygons[polygon]
+            .particles
+            .iter()
+            .enumerate()
+            .map(move |(vertex, particle)| {
+                (PointRef::PolygonVertex { polygon, vertex }, particle.pos)
+            })
+    };
+    let mut contacts = Vec::new();
+    for (a, b) in broad_phase.pairs(&aabbs) {
+        if !filters.collides(bodies[a].body_ref(), bodies[b].body_ref()) {
+            continue;
+        }
+        match (bodies[a], bodies[b]) {
+            (Body::Polygon(p), Body::Polygon(q)) => {
+                for (point, pos) in vertices(p) {
+                    contacts.extend(against_polygon(point, pos, 0.0, q, &polygons[q]));
+                }
+                for (point, pos) in vertices(q) {
+                    contacts.extend(against_polygon(point, pos, 0.0, p, &polygons[p]));
+                }
+            }
+            (Body::Polygon(p), Body::StaticLine(l)) | (Body::StaticLine(l), Body::Polygon(p)) => {
+                for (point, pos) in vertices(p) {
+                    contacts.extend(against_line(point, pos, 0.0, l, lines[l]));
+                }
+            }
+            (Body::Polygon(p), Body::Particle(i)) | (Body::Particle(i), Body::Polygon(p)) => {
+                let point = PointRef::Particle { index: i };
+                contacts.extend(against_polygon(
+                    point,
+                    particles[i].pos,
+                    0.0,
+                    p,
+                    &polygons[p],
+                ));
+            }
+            (Body::Polygon(p), Body::Circle(c)) | (Body::Circle(c), Body::Polygon(p)) => {
+                let point = PointRef::Circle { index: c };
+                let circle = &circles[c];
+                contacts.extend(against_polygon(
+                    point,
+                    circle.point.pos,
+                    circle.radius,
+                    p,
+                    &polygons[p],
+                ));
+            }
+            (Body::Particle(i), Body::StaticLine(l)) | (Body::StaticLine(l), Body::Particle(i)) => {
+                let point = PointRef::Particle { index: i };
+                contacts.extend(against_line(point, particles[i].pos, 0.0, l, lines[l]));
+            }
+            (Body::Circle(c), Body::StaticLine(l)) | (Body::StaticLine(l), Body::Circle(c)) => {
+                let point = PointRef::Circle { index: c };
+                let circle = &circles[c];
+                contacts.extend(against_line(
+                    point,
+                    circle.point.pos,
+                    circle.radius,
+                    l,
+                    lines[l],
+                ));
+            }
+            (Body::Particle(i), Body::Circle(c)) | (Body::Circle(c), Body::Particle(i)) => {
+                let point = PointRef::Particle { index: i };
+                let circle = &circles[c];
+                contacts.extend(against_circle(
+                    point,
+                    particles[i].pos,
+                    0.0,
+                    c,
+                    circle.point.pos,
+                    circle.radius,
+                    velocity(&circle.point),
+                ));
+            }
+            (Body::Circle(c), Body::Circle(d)) => {
+                let (c, d) = (c.min(d), c.max(d));
+                let point = PointRef::Circle { index: c };
+                contacts.extend(against_circle(
+                    point,
+                    circles[c].point.pos,
+                    circles[c].radius,
+                    d,
+                    circles[d].point.pos,
+                    circles[d].radius,
+                    velocity(&circles[d].point),
+                ));
+            }
+            _ => {}
+        }
+    }
+    contacts
+}
@@ -0,0 +1,175 @@
+use crate::contact::Contact;
+use crate::material::Velocities;
+use crate::pick::{point_particle, BodyRef};
+use crate::world::BodyId;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactPhase {
+    /// The bodies touched for the first time since they were last apart.
+    Begin,
+    /// The bodies were already touching the update before.
+    Persist,
+    /// The bodies stopped touching. Position, normal and depth are from
+    /// the last update they touched.
+    End,
+}
+
+/// Two bodies touching during an update.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContactEvent {
+    pub phase: ContactPhase,
+    /// The two bodies, `a` before `b` in `BodyId` order.
+    pub a: BodyId,
+    pub b: BodyId,
+    /// The deepest contact between them, on the surface of the body it was
+    /// found against.
+    pub point: Vector2<f32>,
+    /// Unit direction out of `b` towards `a` at `point`.
+    pub normal: Vector2<f32>,
+    /// How far `a` is pushed into `b` at `point`, negative while they are
+    /// only just touching.
+    pub depth: f32,
+    /// Speed the collision response took off the points of the contact
+    /// along the normal, in units per second, summed over every point.
+    /// Bodies have no mass, so this is the impulse per unit of mass.
+    pub impulse: f32,
+}
+
+pub type ContactListener = Box<dyn FnMut(&ContactEvent)>;
+
+/// Which bodies touched in the last update, and the events since the last
+/// fixed step started.
+#[derive(Default)]
+pub struct ContactTracker {
+    enabled: bool,
+    touching: BTreeMap<(BodyId, BodyId), ContactEvent>,
+    events: Vec<ContactEvent>,
+    listeners: Vec<ContactListener>,
+}
+
+impl ContactTracker {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    /// Calls `listener` with every event as it happens.
+    pub fn add_listener(&mut self, listener: ContactListener) {
+        self.listeners.push(listener);
+    }
+
+    /// Forgets what was touching, without sending `End` events. For when
+    /// the bodies were replaced.
+    pub fn clear(&mut self) {
+        self.touching.clear();
+        self.events.clear();
+    }
+
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn events(&self) -> &[ContactEvent] {
+        &self.events
+    }
+
+    /// Pairs touching in the last update, as their `Begin` or `Persist`
+    /// event.
+    pub fn touching(&self) -> impl Iterator<Item = &ContactEvent> {
+        self.touching.values()
+    }
+
+    /// Compares the pairs touching now against the last update and sends
+    /// out what changed.
+    pub fn update(&mut self, mut touching: BTreeMap<(BodyId, BodyId), ContactEvent>) {
+        let mut events = Vec::new();
+        for (pair, previous) in self.touching.iter() {
+            if !touching.contains_key(pair) {
+                events.push(ContactEvent {
+                    phase: ContactPhase::End,
+                    impulse: 0.0,
+                    ..*previous
+                });
+            }
+        }
+        for (pair, event) in touching.iter_mut() {
+            event.phase = match self.touching.contains_key(pair) {
+                true => ContactPhase::Persist,
+                false => ContactPhase::Begin,
+            };
+            events.push(*event);
+        }
+        self.touching = touching;
+
+        for event in events.iter() {
+            for listener in self.listeners.iter_mut() {
+                listener(event);
+            }
+        }
+        self.events.extend(events);
+    }
+}
+
+/// Groups the contacts of an update of `dt` seconds by body pair, keeping
+/// the deepest contact of each. `before` holds the velocities from the
+/// start of the update, and `id` names the bodies.
+pub fn touching(
+    solver: &Solver,
+    contacts: &[Contact],
+    before: &Velocities,
+    dt: f32,
+    id: impl Fn(BodyRef) -> Option<BodyId>,
+) -> BTreeMap<(BodyId, BodyId), ContactEvent> {
+    let gravity_step = solver.gravity * dt * dt;
+    let mut touching: BTreeMap<(BodyId, BodyId), ContactEvent> = BTreeMap::new();
+    for contact in contacts {
+        let (Some(a), Some(b)) = (id(contact.point.body()), id(contact.other)) else {
+            continue;
+        };
+        let (Some(particle), Some(before)) = (
+            point_particle(solver, contact.point),
+            before.get(contact.point),
+        ) else {
+            continue;
+        };
+        let impulse = (contact.normal_speed(particle)
+            - contact.expected_speed(before, gravity_step))
+        .max(0.0)
+            / dt;
+        let (a, b, normal) = match a < b {
+            true => (a, b, contact.normal),
+            false => (b, a, -contact.normal),
+        };
+        let event = ContactEvent {
+            phase: ContactPhase::Begin,
+            a,
+            b,
+            point: contact.pos,
+            normal,
+            depth: contact.depth,
+            impulse,
+        };
+        touching
+            .entry((a, b))
+            .and_modify(|deepest| {
+                let total = deepest.impulse + impulse;
+                if event.depth > deepest.depth {
+                    *deepest = event;
+                }
+                deepest.impulse = total;
+            })
+            .or_insert(event);
+    }
+    touching
+}
@@ -0,0 +1,82 @@
+use crate::pick::BodyRef;
+use crate::world::BodyTable;
+use bendy2d::solver::{Body, Solver};
+use serde::{Deserialize, Serialize};
+
+/// Which bodies a body collides with. Two bodies collide when each one's
+/// mask has a layer of the other, unless they share a non-zero group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollisionFilter {
+    /// Layers the body is on, one per bit.
+    pub layers: u32,
+    /// Layers the body collides with.
+    pub mask: u32,
+    /// Bodies sharing a group never collide with each other, for example
+    /// the parts of a ragdoll. 0 is no group.
+    pub group: u32,
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self {
+            layers: 1,
+            mask: u32::MAX,
+            group: 0,
+        }
+    }
+}
+
+impl CollisionFilter {
+    pub fn collides(&self, other: &CollisionFilter) -> bool {
+        if self.group != 0 && self.group == other.group {
+            return false;
+        }
+        self.layers & other.mask != 0 && other.layers & self.mask != 0
+    }
+}
+
+/// Collision filters by the current index of each body. The default table
+/// lets everything collide.
+pub type FilterTable = BodyTable<CollisionFilter>;
+
+impl FilterTable {
+    pub fn collides(&self, a: BodyRef, b: BodyRef) -> bool {
+        self.body(a).collides(&self.body(b))
+    }
+
+    /// Whether every body uses the default filter without being listed.
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+            && self.circles.is_empty()
+            && self.polygons.is_empty()
+            && self.static_lines.is_empty()
+    }
+}
+
+pub trait SolverFilter {
+    /// `Solver::update` with every pair of bodies whose filters don't
+    /// collide skipped. The solver asks about each pair in its broad and
+    /// narrow phase, before resolving it, so filtered bodies pass through
+    /// each other untouched: polygons, circles, free particles and static
+    /// lines alike.
+    fn update_filtered(&mut self, dt: f32, filters: &FilterTable);
+}
+
+fn body_ref(body: Body) -> BodyRef {
+    match body {
+        Body::Particle(index) => BodyRef::Particle { index },
+        Body::Circle(index) => BodyRef::Circle { index },
+        Body::Polygon(index) => BodyRef::Polygon { index },
+        Body::StaticLine(index) => BodyRef::StaticLine { index },
+    }
+}
+
+impl SolverFilter for Solver {
+    fn update_filtered(&mut self, dt: f32, filters: &FilterTable) {
+        if filters.is_empty() {
+            self.update(dt);
+            return;
+        }
+        self.update_with_filter(dt, |a, b| filters.collides(body_ref(a), body_ref(b)));
+    }
+}
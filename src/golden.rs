@@ -0,0 +1,268 @@
+use crate::runner;
+use crate::scenarios::{self, Scenario};
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Bumped whenever the file layout changes. Older files are ignored rather
+/// than misread.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Scenarios with a golden trajectory, and how often a frame is kept.
+pub const SCENARIOS: [(&str, usize); 3] =
+    [("deterministic", 10), ("high_speeds", 1), ("pressure", 5)];
+
+/// Recorded polygon particle positions of one scenario.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Trajectory {
+    pub version: u32,
+    pub scenario: String,
+    pub dt: f32,
+    pub steps: usize,
+    /// Particle count of every polygon, in solver order.
+    pub polygons: Vec<usize>,
+    pub frames: Vec<Frame>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Frame {
+    pub frame: usize,
+    pub positions: Vec<Vector2<f32>>,
+}
+
+/// Two values match when `|actual - expected| <= abs + rel * |expected|`.
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+    pub abs: f32,
+    pub rel: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            abs: 1e-4,
+            rel: 1e-5,
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn exact() -> Self {
+        Self { abs: 0.0, rel: 0.0 }
+    }
+
+    pub fn matches(&self, expected: f32, actual: f32) -> bool {
+        (actual - expected).abs() <= self.abs + self.rel * expected.abs()
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    UnknownScenario(String),
+    Missing(PathBuf),
+    /// The file was written in another format version.
+    Version {
+        path: PathBuf,
+        found: u32,
+    },
+    Io(std::io::Error),
+    Layout(String),
+    Diverged {
+        frame: usize,
+        polygon: usize,
+        particle: usize,
+        expected: Vector2<f32>,
+        actual: Vector2<f32>,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::UnknownScenario(name) => write!(f, "unknown scenario {}", name),
+            GoldenError::Missing(path) => write!(
+                f,
+                "no golden file at {}, run `cargo run --bin deterministic -- --bless` to create it",
+                path.display()
+            ),
+            GoldenError::Version { path, found } => write!(
+                f,
+                "golden file {} has format version {}, expected {}, run `cargo run --bin deterministic -- --bless` to regenerate it",
+                path.display(),
+                found,
+                FORMAT_VERSION
+            ),
+            GoldenError::Io(e) => write!(f, "{}", e),
+            GoldenError::Layout(message) => write!(f, "layout changed: {}", message),
+            GoldenError::Diverged {
+                frame,
+                polygon,
+                particle,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "diverged at frame {}, polygon {}, particle {}: expected {}, {} but got {}, {}",
+                frame, polygon, particle, expected.x, expected.y, actual.x, actual.y
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for GoldenError {
+    fn from(e: std::io::Error) -> Self {
+        GoldenError::Io(e)
+    }
+}
+
+pub fn path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{}.v{}.json", name, FORMAT_VERSION))
+}
+
+fn sample_every(name: &str) -> Option<usize> {
+    SCENARIOS
+        .iter()
+        .find(|(scenario, _)| *scenario == name)
+        .map(|(_, sample_every)| *sample_every)
+}
+
+/// Runs the scenario to the end, keeping every `sample_every`th frame.
+pub fn record(scenario: &mut Scenario, sample_every: usize) -> Trajectory {
+    let polygons = scenario
+        .solver
+        .get_polygons()
+        .iter()
+        .map(|polygon| polygon.particles.len())
+        .collect();
+
+    let mut frames = Vec::new();
+    for frame in 0..scenario.steps {
+        scenario.solver.update(scenario.dt);
+        if frame % sample_every == 0 || frame == scenario.steps - 1 {
+            frames.push(Frame {
+                frame,
+                positions: runner::polygon_positions(&scenario.solver),
+            });
+        }
+    }
+
+    Trajectory {
+        version: FORMAT_VERSION,
+        scenario: scenario.name.to_string(),
+        dt: scenario.dt,
+        steps: scenario.steps,
+        polygons,
+        frames,
+    }
+}
+
+/// Finds the first frame, polygon and particle where `actual` leaves
+/// `expected` by more than the tolerance.
+pub fn compare(
+    expected: &Trajectory,
+    actual: &Trajectory,
+    tolerance: Tolerance,
+) -> Result<(), GoldenError> {
+    if expected.polygons != actual.polygons {
+        return Err(GoldenError::Layout(format!(
+            "expected polygon sizes {:?}, got {:?}",
+            expected.polygons, actual.polygons
+        )));
+    }
+    if expected.frames.len() != actual.frames.len() {
+        return Err(GoldenError::Layout(format!(
+            "expected {} frames, got {}",
+            expected.frames.len(),
+            actual.frames.len()
+        )));
+    }
+
+    for (expected_frame, actual_frame) in expected.frames.iter().zip(actual.frames.iter()) {
+        if expected_frame.frame != actual_frame.frame {
+            return Err(GoldenError::Layout(format!(
+                "expected frame {}, got {}",
+                expected_frame.frame, actual_frame.frame
+            )));
+        }
+        let particles: usize = expected.polygons.iter().sum();
+        for (name, frame) in [("expected", expected_frame), ("actual", actual_frame)] {
+            if frame.positions.len() != particles {
+                return Err(GoldenError::Layout(format!(
+                    "{} frame {} has {} positions, the polygons have {} particles",
+                    name,
+                    frame.frame,
+                    frame.positions.len(),
+                    particles
+                )));
+            }
+        }
+        let mut offset = 0;
+        for (polygon, particle_count) in expected.polygons.iter().enumerate() {
+            for particle in 0..*particle_count {
+                let expected_pos = expected_frame.positions[offset + particle];
+                let actual_pos = actual_frame.positions[offset + particle];
+                if !tolerance.matches(expected_pos.x, actual_pos.x)
+                    || !tolerance.matches(expected_pos.y, actual_pos.y)
+                {
+                    return Err(GoldenError::Diverged {
+                        frame: actual_frame.frame,
+                        polygon,
+                        particle,
+                        expected: expected_pos,
+                        actual: actual_pos,
+                    });
+                }
+            }
+            offset += particle_count;
+        }
+    }
+    Ok(())
+}
+
+pub fn load(name: &str) -> Result<Trajectory, GoldenError> {
+    let path = path(name);
+    if !path.exists() {
+        return Err(GoldenError::Missing(path));
+    }
+    let mut data = String::new();
+    File::open(&path)?.read_to_string(&mut data)?;
+    let trajectory: Trajectory = serde_json::from_str(&data).map_err(std::io::Error::from)?;
+    if trajectory.version != FORMAT_VERSION {
+        return Err(GoldenError::Version {
+            path,
+            found: trajectory.version,
+        });
+    }
+    Ok(trajectory)
+}
+
+fn record_by_name(name: &str) -> Result<Trajectory, GoldenError> {
+    let unknown = || GoldenError::UnknownScenario(name.to_string());
+    let sample_every = sample_every(name).ok_or_else(unknown)?;
+    let mut scenario = scenarios::by_name(name).ok_or_else(unknown)?;
+    Ok(record(&mut scenario, sample_every))
+}
+
+/// Runs a named scenario and compares it against its golden file.
+pub fn check(name: &str, tolerance: Tolerance) -> Result<(), GoldenError> {
+    let expected = load(name)?;
+    let actual = record_by_name(name)?;
+    compare(&expected, &actual, tolerance)
+}
+
+/// Runs a named scenario and overwrites its golden file with the result.
+pub fn bless(name: &str) -> Result<(), GoldenError> {
+    let trajectory = record_by_name(name)?;
+    let path = path(name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let data = serde_json::to_string(&trajectory).map_err(std::io::Error::from)?;
+    File::create(&path)?.write_all(data.as_bytes())?;
+    Ok(())
+}
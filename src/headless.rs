@@ -0,0 +1,102 @@
+use bendy2d_testing::broad_phase;
+use bendy2d_testing::ccd;
+use bendy2d_testing::replay::{self, Recording};
+use bendy2d_testing::runner;
+use bendy2d_testing::scenarios;
+
+/// Runs one of the windowed test scenarios without macroquad and writes the
+/// same CSV output the windowed binary would.
+///
+/// Usage: `headless <scenario>` where scenario is one of `scenarios::NAMES`,
+/// or `headless replay <file>` to rebuild a recorded testbed session.
+/// `high_speeds` runs with swept collisions and exits non-zero if a box
+/// tunnels through the other or stays stuck inside it.
+fn main() {
+    let name = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "deterministic".to_string());
+    if name == "replay" {
+        let path = std::env::args()
+            .nth(2)
+            .unwrap_or_else(|| "replay.json".to_string());
+        run_replay(&path);
+        return;
+    }
+    let mut scenario = match scenarios::by_name(&name) {
+        Some(scenario) => scenario,
+        None => {
+            println!(
+                "Unknown scenario: {}, expected one of {}",
+                name,
+                scenarios::NAMES.join(", ")
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let name = scenario.name;
+    match name {
+        "high_speeds" => match ccd::check_swept(&mut scenario) {
+            Ok(()) => println!("high_speeds: no tunnelling or stuck penetrations"),
+            Err(e) => {
+                println!("high_speeds failed: {}", e);
+                std::process::exit(1);
+            }
+        },
+        "deterministic" | "pressure" => {
+            let positions = runner::run_positions(&mut scenario);
+            let path = match name {
+                "deterministic" => "positions.csv".to_string(),
+                _ => format!("{}.csv", name),
+            };
+            if let Err(e) = runner::write_positions_csv(&path, &positions) {
+                println!("Error creating file: {}", e);
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            let latencies = runner::run_latencies(&mut scenario);
+            for latency in latencies.iter() {
+                println!("{}", latency);
+            }
+            println!("{}", broad_phase::pair_diagnostics(&scenario.solver));
+            let path = format!("{}.csv", scenario.name);
+            if let Err(e) = runner::write_latencies_csv(&path, &latencies) {
+                println!("Error creating file: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Rebuilds the world of a testbed recording and writes the final polygon
+/// particle positions to `replay.csv`.
+fn run_replay(path: &str) {
+    let recording = match Recording::load(path) {
+        Ok(recording) => recording,
+        Err(e) => {
+            println!("Error loading replay {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    let last_frame = recording.last_frame();
+    let solver = match replay::replay(recording) {
+        Ok(world) => world,
+        Err(e) => {
+            println!("Error replaying {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Replayed {} frames: {} polygons, {} particles, {} circles",
+        last_frame,
+        solver.get_polygons_len(),
+        solver.get_particle_len(),
+        solver.get_circles_len()
+    );
+    let positions = vec![runner::polygon_positions(&solver)];
+    if let Err(e) = runner::write_positions_csv("replay.csv", &positions) {
+        println!("Error creating file: {}", e);
+        std::process::exit(1);
+    }
+}
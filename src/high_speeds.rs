@@ -1,51 +1,18 @@
-use bendy2d::polygon::Polygon;
-use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::broad_phase::BroadPhase;
+use bendy2d_testing::ccd::{self, SolverCcd};
+use bendy2d_testing::filter::FilterTable;
+use bendy2d_testing::scenarios;
+use bendy2d_testing::threads::Threads;
 use macroquad::prelude::*;
-use nalgebra::Vector2;
 
 #[macroquad::main("BasicShapes")]
 async fn main() {
-    let screen_size = Vector2::new(1920.0, 1080.0);
+    let screen_size = scenarios::screen_size();
     request_new_screen_size(screen_size.x, screen_size.y);
-    let scale = 30.0;
 
-    let mut solver = Solver::new();
-    solver.bounds = Bounds {
-        pos: Vector2::new(0.0, 0.0),
-        size: screen_size / scale,
-    };
-
-    let size = Vector2::new(100.0, 100.0);
-    let pos1 = Vector2::new(200.0, 200.0);
-    let pos2 = Vector2::new(1600.0, 200.0);
-    solver.add_polygon(Polygon::new_box(
-        pos1 / scale,
-        0.0,
-        size / scale,
-        4.0,
-        50.0,
-        false,
-        -1.0,
-    ));
-
-    solver.add_polygon(Polygon::new_box(
-        pos2 / scale,
-        0.0,
-        size / scale,
-        4.0,
-        50.0,
-        false,
-        -1.0,
-    ));
-
-    let polygon1 = solver.get_polygon_mut(0).unwrap();
-    for particle in polygon1.particles.iter_mut() {
-        particle.prev_pos = particle.pos - Vector2::new(10.0, 0.0);
-    }
-    let polygon2 = solver.get_polygon_mut(1).unwrap();
-    for particle in polygon2.particles.iter_mut() {
-        particle.prev_pos = particle.pos + Vector2::new(10.0, 0.0);
-    }
+    let mut scenario = scenarios::high_speeds();
+    let scale = scenario.scale;
+    let mut swept = true;
 
     // Refresh window
     clear_background(RED);
@@ -53,11 +20,23 @@ async fn main() {
     loop {
         clear_background(WHITE);
 
+        if is_key_pressed(KeyCode::C) {
+            swept = !swept;
+        }
         if is_key_pressed(KeyCode::Space) || is_key_down(KeyCode::W) {
-            solver.update(1.0 / 60.0);
+            if swept {
+                scenario.solver.update_swept(
+                    scenario.dt,
+                    BroadPhase::default(),
+                    &Threads::default(),
+                    &FilterTable::default(),
+                );
+            } else {
+                scenario.solver.update(scenario.dt);
+            }
         }
 
-        for (i, polygon) in solver.get_polygons().iter().enumerate() {
+        for (i, polygon) in scenario.solver.get_polygons().iter().enumerate() {
             let particle_count = polygon.particles.len();
             for (j, point) in polygon.particles.iter().enumerate() {
                 draw_triangle(
@@ -72,6 +51,26 @@ async fn main() {
             }
         }
 
+        let penetrations = ccd::penetrations(&scenario.solver);
+        for penetration in penetrations.iter() {
+            let pos = scenario.solver.get_polygons()[penetration.polygon].particles
+                [penetration.particle]
+                .pos;
+            draw_circle(pos.x * scale, pos.y * scale, 5.0, YELLOW);
+        }
+        draw_text(
+            format!(
+                "Swept collisions (C): {}, penetrating points: {}",
+                if swept { "on" } else { "off" },
+                penetrations.len()
+            )
+            .as_str(),
+            10.0,
+            30.0,
+            30.0,
+            BLACK,
+        );
+
         next_frame().await
     }
 }
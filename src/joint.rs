@@ -0,0 +1,273 @@
+use crate::shape_match::{center, fit};
+use crate::world::PolygonId;
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::Solver;
+use nalgebra::{Rotation2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Passes over every joint per update. Joints sharing a polygon pull
+/// against each other, so one pass leaves them visibly apart.
+const ITERATIONS: usize = 4;
+
+/// One end of a joint, as given when it is made.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anchor {
+    /// The point of the polygon that is at `pos` when the joint is made.
+    Polygon {
+        polygon: PolygonId,
+        pos: Vector2<f32>,
+    },
+    /// A fixed point in the world.
+    World { pos: Vector2<f32> },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JointKind {
+    /// Keeps the two anchors together and lets the bodies turn. `limits`
+    /// bounds how far `b` may turn relative to `a`, in radians from where
+    /// it was when the joint was made.
+    Revolute { limits: Option<(f32, f32)> },
+    /// Keeps the bodies from turning relative to each other and lets the
+    /// anchor of `b` slide along `axis` through the anchor of `a`. `axis`
+    /// turns with `a`, and `limits` bounds the distance slid.
+    Prismatic {
+        axis: Vector2<f32>,
+        limits: Option<(f32, f32)>,
+    },
+    /// Keeps the anchors together and the bodies from turning relative to
+    /// each other.
+    Weld,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JointDesc {
+    pub a: Anchor,
+    pub b: Anchor,
+    pub kind: JointKind,
+}
+
+/// Where a polygon is and how far it has turned, fitted to its vertices.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    center: Vector2<f32>,
+    angle: f32,
+}
+
+impl Frame {
+    fn to_world(&self, local: Vector2<f32>) -> Vector2<f32> {
+        self.center + Rotation2::new(self.angle) * local
+    }
+}
+
+/// A joint end after it was made. Polygon ends keep the shape the polygon
+/// had then, to measure how far it has turned since.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum End {
+    Polygon {
+        polygon: PolygonId,
+        // Vertex offsets from the center when the joint was made
+        rest: Vec<Vector2<f32>>,
+        local: Vector2<f32>,
+    },
+    World {
+        pos: Vector2<f32>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Joint {
+    a: End,
+    b: End,
+    kind: JointKind,
+}
+
+/// The rotation that best maps `rest` onto the current vertices.
+fn frame(polygon: &Polygon, rest: &[Vector2<f32>]) -> Frame {
+    let (center, angle) = fit(polygon, rest);
+    Frame { center, angle }
+}
+
+fn wrap(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+impl End {
+    fn new<'a, E>(
+        anchor: Anchor,
+        polygon: &impl Fn(PolygonId) -> Result<&'a Polygon, E>,
+    ) -> Result<Self, E> {
+        match anchor {
+            Anchor::Polygon { polygon: id, pos } => {
+                let polygon = polygon(id)?;
+                let center = center(polygon);
+                Ok(End::Polygon {
+                    polygon: id,
+                    rest: polygon.particles.iter().map(|p| p.pos - center).collect(),
+                    local: pos - center,
+                })
+            }
+            Anchor::World { pos } => Ok(End::World { pos }),
+        }
+    }
+
+    fn polygon(&self) -> Option<PolygonId> {
+        match self {
+            End::Polygon { polygon, .. } => Some(*polygon),
+            End::World { .. } => None,
+        }
+    }
+
+    /// The frame of the end and where its anchor is now.
+    fn locate(
+        &self,
+        solver: &Solver,
+        index: &impl Fn(PolygonId) -> Option<usize>,
+    ) -> Option<(Frame, Vector2<f32>)> {
+        match self {
+            End::Polygon {
+                polygon,
+                rest,
+                local,
+            } => {
+                let polygon = solver.get_polygons().get(index(*polygon)?)?;
+                let frame = frame(polygon, rest);
+                Some((frame, frame.to_world(*local)))
+            }
+            End::World { pos } => Some((
+                Frame {
+                    center: *pos,
+                    angle: 0.0,
+                },
+                *pos,
+            )),
+        }
+    }
+}
+
+/// Moves and turns a polygon end by `weight` of a correction. Only
+/// positions change, so the polygon picks the motion up as velocity.
+fn correct(
+    solver: &mut Solver,
+    index: &impl Fn(PolygonId) -> Option<usize>,
+    end: &End,
+    frame: &Frame,
+    offset: Vector2<f32>,
+    turn: f32,
+) {
+    let Some(polygon) = end
+        .polygon()
+        .and_then(index)
+        .and_then(|polygon| solver.get_polygon_mut(polygon))
+    else {
+        return;
+    };
+    let rotation = Rotation2::new(turn);
+    for particle in polygon.particles.iter_mut() {
+        particle.pos = frame.center + rotation * (particle.pos - frame.center) + offset;
+    }
+}
+
+impl Joint {
+    /// Makes a joint from its description, looking up the polygons it
+    /// joins with `polygon`.
+    pub fn new<'a, E>(
+        desc: &JointDesc,
+        polygon: impl Fn(PolygonId) -> Result<&'a Polygon, E>,
+    ) -> Result<Self, E> {
+        Ok(Self {
+            a: End::new(desc.a, &polygon)?,
+            b: End::new(desc.b, &polygon)?,
+            kind: desc.kind,
+        })
+    }
+
+    pub fn kind(&self) -> JointKind {
+        self.kind
+    }
+
+    /// Whether the joint holds on to `polygon`.
+    pub fn joins(&self, polygon: PolygonId) -> bool {
+        self.a.polygon() == Some(polygon) || self.b.polygon() == Some(polygon)
+    }
+
+    /// Where the two anchors are now.
+    pub fn anchors(
+        &self,
+        solver: &Solver,
+        index: impl Fn(PolygonId) -> Option<usize>,
+    ) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        let (_, a) = self.a.locate(solver, &index)?;
+        let (_, b) = self.b.locate(solver, &index)?;
+        Some((a, b))
+    }
+
+    fn solve(&self, solver: &mut Solver, index: &impl Fn(PolygonId) -> Option<usize>) {
+        let (Some((frame_a, anchor_a)), Some((frame_b, anchor_b))) =
+            (self.a.locate(solver, index), self.b.locate(solver, index))
+        else {
+            return;
+        };
+        // Share of each correction taken by each end, nothing for the world
+        let (weight_a, weight_b) = match (self.a.polygon(), self.b.polygon()) {
+            (Some(_), Some(_)) => (0.5, 0.5),
+            (Some(_), None) => (1.0, 0.0),
+            (None, Some(_)) => (0.0, 1.0),
+            (None, None) => return,
+        };
+
+        let error = anchor_b - anchor_a;
+        let relative_angle = wrap(frame_b.angle - frame_a.angle);
+        let (pull, turn) = match self.kind {
+            JointKind::Revolute { limits } => {
+                let turn = match limits {
+                    Some((min, max)) => relative_angle - relative_angle.clamp(min, max),
+                    None => 0.0,
+                };
+                (error, turn)
+            }
+            JointKind::Prismatic { axis, limits } => {
+                let axis = (Rotation2::new(frame_a.angle) * axis)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(Vector2::x());
+                let along = error.dot(&axis);
+                let allowed = match limits {
+                    Some((min, max)) => along.clamp(min, max),
+                    None => along,
+                };
+                (error - axis * allowed, relative_angle)
+            }
+            JointKind::Weld => (error, relative_angle),
+        };
+
+        correct(
+            solver,
+            index,
+            &self.a,
+            &frame_a,
+            pull * weight_a,
+            turn * weight_a,
+        );
+        correct(
+            solver,
+            index,
+            &self.b,
+            &frame_b,
+            -pull * weight_b,
+            -turn * weight_b,
+        );
+    }
+}
+
+/// Pulls every joint back together after a solver update. `index` finds
+/// the current index of a polygon.
+pub fn solve(solver: &mut Solver, joints: &[Joint], index: impl Fn(PolygonId) -> Option<usize>) {
+    for _ in 0..ITERATIONS {
+        for joint in joints {
+            joint.solve(solver, &index);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::contact::Contact;
+use crate::pick::{point_particle_mut, BodyRef};
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::Solver;
+use nalgebra::{Rotation2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Offset and turn of a kinematic polygon from where it was when it became
+/// kinematic, about its center at that time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub translation: Vector2<f32>,
+    pub rotation: f32,
+}
+
+/// How a kinematic polygon moves over time.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Motion {
+    /// Stays where it is.
+    Fixed,
+    /// Moves by `velocity` units per second.
+    Linear { velocity: Vector2<f32> },
+    /// Turns by `angular_velocity` radians per second.
+    Spin { angular_velocity: f32 },
+    /// Swings back and forth by up to `amplitude`, once per `period` seconds.
+    Oscillate {
+        amplitude: Vector2<f32>,
+        period: f32,
+    },
+    /// Holds the transform last given with `World::set_kinematic_transform`,
+    /// for motion scripted step by step. The polygon moves with the
+    /// velocity of going from the previous transform to the new one in one
+    /// update.
+    Manual(Transform),
+}
+
+impl Motion {
+    pub fn transform(&self, time: f32) -> Transform {
+        match *self {
+            Motion::Fixed => Transform::default(),
+            Motion::Linear { velocity } => Transform {
+                translation: velocity * time,
+                rotation: 0.0,
+            },
+            Motion::Spin { angular_velocity } => Transform {
+                translation: Vector2::zeros(),
+                rotation: angular_velocity * time,
+            },
+            Motion::Oscillate { amplitude, period } => Transform {
+                translation: amplitude * (2.0 * PI * time / period.max(f32::EPSILON)).sin(),
+                rotation: 0.0,
+            },
+            Motion::Manual(transform) => transform,
+        }
+    }
+}
+
+/// A polygon whose vertices are placed by its motion every update instead
+/// of being simulated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Kinematic {
+    pub motion: Motion,
+    origin: Vector2<f32>,
+    // Vertex offsets from origin when the polygon became kinematic
+    rest: Vec<Vector2<f32>>,
+    time: f32,
+    // Transform the vertices were last placed at, None until the first
+    // update
+    placed: Option<Transform>,
+}
+
+impl Kinematic {
+    pub fn new(polygon: &Polygon, motion: Motion) -> Self {
+        let sum: Vector2<f32> = polygon.particles.iter().map(|p| p.pos).sum();
+        let origin = sum / polygon.particles.len().max(1) as f32;
+        Self {
+            motion,
+            origin,
+            rest: polygon.particles.iter().map(|p| p.pos - origin).collect(),
+            time: 0.0,
+            placed: None,
+        }
+    }
+
+    /// Seconds since the polygon became kinematic.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn positions(&self, transform: Transform) -> impl Iterator<Item = Vector2<f32>> + '_ {
+        let rotation = Rotation2::new(transform.rotation);
+        self.rest
+            .iter()
+            .map(move |rest| self.origin + transform.translation + rotation * rest)
+    }
+
+    /// Puts the vertices where the motion has them at the current time,
+    /// moving from where they were last placed. The first time, they move
+    /// as the motion had them over the last `dt` seconds.
+    pub fn place(&mut self, polygon: &mut Polygon, dt: f32) {
+        let transform = self.motion.transform(self.time);
+        let last = self
+            .placed
+            .unwrap_or_else(|| self.motion.transform(self.time - dt));
+        self.placed = Some(transform);
+        let now = self.positions(transform);
+        let before = self.positions(last);
+        for (particle, (pos, prev_pos)) in polygon.particles.iter_mut().zip(now.zip(before)) {
+            particle.pos = pos;
+            particle.prev_pos = prev_pos;
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.time += dt;
+    }
+}
+
+/// Pushes every point found inside a kinematic polygon back out to its
+/// surface. The solver shares each separation between both sides, and the
+/// kinematic side is put back afterwards, so without this the other body
+/// would only get part of the push.
+pub fn push_out(solver: &mut Solver, contacts: &[Contact], kinematic: impl Fn(usize) -> bool) {
+    for contact in contacts {
+        let BodyRef::Polygon { index } = contact.other else {
+            continue;
+        };
+        if contact.depth <= 0.0 || !kinematic(index) || kinematic_point(contact, &kinematic) {
+            continue;
+        }
+        if let Some(particle) = point_particle_mut(solver, contact.point) {
+            particle.pos += contact.normal * contact.depth;
+        }
+    }
+}
+
+fn kinematic_point(contact: &Contact, kinematic: &impl Fn(usize) -> bool) -> bool {
+    match contact.point.body() {
+        BodyRef::Polygon { index } => kinematic(index),
+        _ => false,
+    }
+}
@@ -0,0 +1,25 @@
+pub mod bench;
+pub mod broad_phase;
+pub mod ccd;
+pub mod connector;
+pub mod contact;
+pub mod events;
+pub mod filter;
+pub mod golden;
+pub mod joint;
+pub mod kinematic;
+pub mod material;
+pub mod outline;
+pub mod pick;
+pub mod plasticity;
+pub mod pressure;
+pub mod query;
+pub mod remove;
+pub mod replay;
+pub mod runner;
+pub mod scenarios;
+pub mod scene;
+pub mod shape_match;
+pub mod snapshot;
+pub mod threads;
+pub mod world;
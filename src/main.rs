@@ -1,16 +1,34 @@
-use bendy2d::circle::Circle;
 use bendy2d::common::{is_point_in_polygon, proj_point_on_line};
 
-use bendy2d::link::{CircleLink, Link, ParticleLink};
-use bendy2d::particle::Particle;
 use bendy2d::polygon::{Collision, Polygon};
 use bendy2d::solver::Solver;
-use bendy2d::spring::Spring;
+use bendy2d_testing::connector::{Connector, ConnectorKind, Endpoint};
+use bendy2d_testing::events::{ContactEvent, ContactPhase};
+use bendy2d_testing::filter::CollisionFilter;
+use bendy2d_testing::joint::{Anchor, JointDesc, JointKind};
+use bendy2d_testing::kinematic::Motion;
+use bendy2d_testing::material::Material;
+use bendy2d_testing::pick::{self, BodyRef, PointRef};
+use bendy2d_testing::plasticity;
+use bendy2d_testing::pressure::{Pressure, PressureModel};
+use bendy2d_testing::query::SolverQuery;
+use bendy2d_testing::replay::{Action, Recording, Replayer, Settings};
+use bendy2d_testing::scene::{CircleDesc, PolygonShape, Scene, SceneBounds, SpringDesc};
+use bendy2d_testing::threads;
+use bendy2d_testing::world::{
+    CircleId, CircleLink, ConnectorId, ParticleId, ParticleLink, Spring, World, WorldSnapshot,
+};
 use egui_macroquad::egui::Pos2;
 use egui_macroquad::{egui, ui};
 use macroquad::math::{f32, u32};
 use macroquad::prelude::*;
 use nalgebra::Vector2;
+use std::collections::VecDeque;
+
+// Number of steps that can be rewound
+const HISTORY_LEN: usize = 600;
+// Begin and end contact events kept for the information window
+const CONTACT_LOG_LEN: usize = 20;
 
 // TODO: remove this
 pub enum CollisionPhase {
@@ -368,6 +386,42 @@ impl CollisionPhase {
     }
 }
 
+enum Tool {
+    Spawn,
+    Grab,
+    Select,
+    Erase,
+    Ray,
+    Joint,
+    Connect,
+}
+
+impl Tool {
+    fn name(&self) -> &str {
+        match *self {
+            Tool::Spawn => "Spawn",
+            Tool::Grab => "Grab",
+            Tool::Select => "Select",
+            Tool::Erase => "Erase",
+            Tool::Ray => "Ray",
+            Tool::Joint => "Joint",
+            Tool::Connect => "Connect",
+        }
+    }
+
+    fn increase(&mut self) {
+        *self = match *self {
+            Tool::Spawn => Tool::Grab,
+            Tool::Grab => Tool::Select,
+            Tool::Select => Tool::Erase,
+            Tool::Erase => Tool::Ray,
+            Tool::Ray => Tool::Joint,
+            Tool::Joint => Tool::Connect,
+            Tool::Connect => Tool::Spawn,
+        }
+    }
+}
+
 enum SpawnMode {
     Single,
     Grid,
@@ -395,11 +449,55 @@ impl SpawnMode {
     }
 }
 
+enum JointType {
+    Revolute,
+    LimitedRevolute,
+    Prismatic,
+    Weld,
+}
+
+impl JointType {
+    fn name(&self) -> &str {
+        match *self {
+            JointType::Revolute => "Revolute",
+            JointType::LimitedRevolute => "Revolute, +-45 degrees",
+            JointType::Prismatic => "Prismatic",
+            JointType::Weld => "Weld",
+        }
+    }
+
+    fn increase(&mut self) {
+        *self = match *self {
+            JointType::Revolute => JointType::LimitedRevolute,
+            JointType::LimitedRevolute => JointType::Prismatic,
+            JointType::Prismatic => JointType::Weld,
+            JointType::Weld => JointType::Revolute,
+        }
+    }
+
+    /// The joint from `a` to `b`. Prismatic joints slide along the line
+    /// between the two clicks.
+    fn kind(&self, a: Vector2<f32>, b: Vector2<f32>) -> JointKind {
+        match *self {
+            JointType::Revolute => JointKind::Revolute { limits: None },
+            JointType::LimitedRevolute => JointKind::Revolute {
+                limits: Some((-std::f32::consts::FRAC_PI_4, std::f32::consts::FRAC_PI_4)),
+            },
+            JointType::Prismatic => JointKind::Prismatic {
+                axis: (b - a).try_normalize(1e-6).unwrap_or(Vector2::x()),
+                limits: None,
+            },
+            JointType::Weld => JointKind::Weld,
+        }
+    }
+}
+
 enum SpawnType {
     Particle,
     Circle,
     Polygon,
     PressurePolygon,
+    Outline,
     Static,
 }
 
@@ -410,6 +508,7 @@ impl SpawnType {
             SpawnType::Circle => "Circle",
             SpawnType::Polygon => "Polygon",
             SpawnType::PressurePolygon => "PressurePolygon",
+            SpawnType::Outline => "Outline",
             SpawnType::Static => "Static",
         }
     }
@@ -419,7 +518,8 @@ impl SpawnType {
             SpawnType::Particle => SpawnType::Circle,
             SpawnType::Circle => SpawnType::Polygon,
             SpawnType::Polygon => SpawnType::PressurePolygon,
-            SpawnType::PressurePolygon => SpawnType::Static,
+            SpawnType::PressurePolygon => SpawnType::Outline,
+            SpawnType::Outline => SpawnType::Static,
             SpawnType::Static => SpawnType::Particle,
         }
     }
@@ -443,6 +543,14 @@ impl TestCase {
             TestCase::Circle2 => "Circle2",
         }
     }
+    fn scene_path(&self) -> Option<&'static str> {
+        match *self {
+            TestCase::Triangle1 => Some("scenes/triangle1.json"),
+            TestCase::Triangle2 => Some("scenes/triangle2.json"),
+            _ => None,
+        }
+    }
+
     fn increase(&mut self) {
         *self = match *self {
             TestCase::Playground => TestCase::Triangle1,
@@ -455,118 +563,114 @@ impl TestCase {
 }
 
 fn spawn_particle_array(
-    solver: &mut Solver,
+    testbed: &mut Testbed,
     pos: Vector2<f32>,
     count: Vector2<u32>,
     dist: f32,
     stiffness: f32,
     permanence_threshold: f32,
 ) {
+    let mut ids = Vec::with_capacity((count.x * count.y) as usize);
+    let spring = |particle_a, particle_b, rest_length| {
+        Action::AddSpring(Spring {
+            particle_a,
+            particle_b,
+            rest_length,
+            stiffness,
+            permanence_threshold,
+        })
+    };
+    let diagonal = (dist * dist + dist * dist).sqrt();
     for y in 0..count.y {
         for x in 0..count.x {
             let particle_pos = Vector2::new(pos.x + x as f32 * dist, pos.y + y as f32 * dist);
-            solver.add_particle(particle_pos);
-            let length = solver.get_particle_len();
+            let id = testbed.add_particle(particle_pos);
+            let at = |x: u32, y: u32| ids[(y * count.x + x) as usize];
             if x > 0 {
-                solver.add_particle_spring(Spring {
-                    particle_a: length - 2,
-                    particle_b: length - 1,
-                    rest_length: dist,
-                    stiffness,
-                    permanence_threshold,
-                });
+                testbed.apply(spring(at(x - 1, y), id, dist));
             }
             if y > 0 {
-                solver.add_particle_spring(Spring {
-                    particle_a: length - count.x as usize - 1,
-                    particle_b: length - 1,
-                    rest_length: dist,
-                    stiffness,
-                    permanence_threshold,
-                });
+                testbed.apply(spring(at(x, y - 1), id, dist));
                 if x < count.x - 1 {
-                    solver.add_particle_spring(Spring {
-                        particle_a: length - count.x as usize,
-                        particle_b: length - 1,
-                        rest_length: (dist * dist + dist * dist).sqrt(),
-                        stiffness,
-                        permanence_threshold,
-                    });
+                    testbed.apply(spring(at(x + 1, y - 1), id, diagonal));
                 }
             }
             if x > 0 && y > 0 {
-                solver.add_particle_spring(Spring {
-                    particle_a: length - count.x as usize - 2,
-                    particle_b: length - 1,
-                    rest_length: (dist * dist + dist * dist).sqrt(),
-                    stiffness,
-                    permanence_threshold,
-                });
+                testbed.apply(spring(at(x - 1, y - 1), id, diagonal));
             }
+            ids.push(id);
         }
     }
 }
 
 fn spawn_circle_array(
-    solver: &mut Solver,
+    testbed: &mut Testbed,
     pos: Vector2<f32>,
     count: Vector2<u32>,
     dist: f32,
     radius: f32,
 ) {
+    let mut ids = Vec::with_capacity((count.x * count.y) as usize);
+    let link = |circle_a, circle_b, target_distance| {
+        Action::AddCircleLink(CircleLink {
+            circle_a,
+            circle_b,
+            target_distance,
+        })
+    };
+    let diagonal = (dist * dist + dist * dist).sqrt();
     for y in 0..count.y {
         for x in 0..count.x {
             let circle_pos = Vector2::new(pos.x + x as f32 * dist, pos.y + y as f32 * dist);
-            solver.add_circle(Circle {
-                point: Particle::new(circle_pos),
+            let id = testbed.add_circle(CircleDesc {
+                pos: circle_pos,
+                prev_pos: None,
                 radius,
             });
-            let length = solver.get_circles_len();
+            let at = |x: u32, y: u32| ids[(y * count.x + x) as usize];
             if x > 0 {
-                solver.add_circle_link(CircleLink {
-                    link: Link {
-                        particle_a: length - 2,
-                        particle_b: length - 1,
-                        target_distance: dist,
-                    },
-                });
+                testbed.apply(link(at(x - 1, y), id, dist));
             }
             if y > 0 {
-                solver.add_circle_link(CircleLink {
-                    link: Link {
-                        particle_a: length - count.x as usize - 1,
-                        particle_b: length - 1,
-                        target_distance: dist,
-                    },
-                });
+                testbed.apply(link(at(x, y - 1), id, dist));
                 if x < count.x - 1 {
-                    solver.add_circle_link(CircleLink {
-                        link: Link {
-                            particle_a: length - count.x as usize,
-                            particle_b: length - 1,
-                            target_distance: (dist * dist + dist * dist).sqrt(),
-                        },
-                    });
+                    testbed.apply(link(at(x + 1, y - 1), id, diagonal));
                 }
             }
             if x > 0 && y > 0 {
-                solver.add_circle_link(CircleLink {
-                    link: Link {
-                        particle_a: length - count.x as usize - 2,
-                        particle_b: length - 1,
-                        target_distance: (dist * dist + dist * dist).sqrt(),
-                    },
-                });
+                testbed.apply(link(at(x - 1, y - 1), id, diagonal));
             }
+            ids.push(id);
         }
     }
 }
 
+struct HistoryEntry {
+    frame: usize,
+    polygon_shapes: Vec<PolygonShape>,
+    snapshot: WorldSnapshot,
+    // Grab spring in the snapshot, taken out again after rewinding to it
+    grab: Option<ConnectorId>,
+}
+
 struct Testbed {
-    solver: Solver,
+    world: World,
     gravity: Vector2<f32>,
+    // Constructor of every polygon in the solver, needed to save scenes
+    polygon_shapes: Vec<PolygonShape>,
+    scene_path: String,
+    // Solver steps taken since the last reset of the counter
+    frame: usize,
+    recording: Option<Recording>,
+    replayer: Option<Replayer>,
+    replay_path: String,
+    last_settings: Settings,
+    history: VecDeque<HistoryEntry>,
+    // Index into history while rewound, None when at the latest step
+    history_cursor: Option<usize>,
 
     radius: f32,
+    tool: Tool,
     spawn_mode: SpawnMode,
     test_case: TestCase,
     spawn_type: SpawnType,
@@ -584,11 +688,29 @@ struct Testbed {
     pause: bool,
     step: bool,
     mouse_pos: Vector2<f32>,
+    prev_mouse_pos: Vector2<f32>,
+    // Grabbed point and the spring pulling it to the mouse
+    grabbed: Option<(PointRef, ConnectorId)>,
+    grab_stiffness: f32,
+    selected: Option<BodyRef>,
+    // Start and end of the ray cast with the ray tool
+    ray: Option<(Vector2<f32>, Vector2<f32>)>,
+    joint_type: JointType,
+    // First anchor of the joint being made with the joint tool
+    joint_start: Option<Anchor>,
+    // Whether the connect tool makes springs instead of links
+    connect_spring: bool,
+    // First end of the connector being made with the connect tool
+    connect_start: Option<Endpoint>,
+    // Vertices clicked so far for the outline spawn type
+    outline: Vec<Vector2<f32>>,
     draw_aabb: bool,
     dt: f32,
     collision_phase: CollisionPhase,
     collision_index: usize,
     collisions: Vec<Collision>,
+    contact_log: VecDeque<ContactEvent>,
+    springs_broken: usize,
 }
 
 impl Testbed {
@@ -616,9 +738,25 @@ impl Testbed {
         let dt = 0.0;
 
         Self {
-            solver,
+            world: World::from_solver(solver),
             gravity,
+            polygon_shapes: Vec::new(),
+            scene_path: "scene.json".to_string(),
+            frame: 0,
+            recording: None,
+            replayer: None,
+            replay_path: "replay.json".to_string(),
+            last_settings: Settings {
+                radius,
+                point_count,
+                stiffness,
+                pressure: 1.0,
+                permanence_threshold: -1.0,
+            },
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            history_cursor: None,
             radius,
+            tool: Tool::Spawn,
             spawn_mode,
             test_case,
             spawn_type,
@@ -634,18 +772,31 @@ impl Testbed {
             pause,
             step,
             mouse_pos,
+            prev_mouse_pos: mouse_pos,
+            grabbed: None,
+            grab_stiffness: 5000.0,
+            selected: None,
+            ray: None,
+            joint_type: JointType::Revolute,
+            joint_start: None,
+            connect_spring: false,
+            connect_start: None,
+            outline: Vec::new(),
             draw_aabb: false,
             dt,
             collision_phase: CollisionPhase::Points,
             collision_index: 0,
             collisions: Vec::<Collision>::new(),
+            contact_log: VecDeque::with_capacity(CONTACT_LOG_LEN),
+            springs_broken: 0,
         }
     }
 
     fn update(&mut self) {
-        self.dt = 0.01; //get_frame_time();
+        self.dt = get_frame_time();
         {
             let _mouse_pos = mouse_position();
+            self.prev_mouse_pos = self.mouse_pos;
             self.mouse_pos = Vector2::<f32>::new(_mouse_pos.0, _mouse_pos.1);
         }
 
@@ -657,23 +808,29 @@ impl Testbed {
                     self.radius -= 1.0;
                 }
                 self.overlay_clear();
-                self.handle_input();
+                if self.replayer.is_none() {
+                    self.handle_input();
+                }
+            }
+
+            if self.replayer.is_none() {
+                let settings = self.settings();
+                if settings != self.last_settings {
+                    self.apply(Action::Settings(settings));
+                }
             }
 
             if !self.pause || self.step {
+                let steps = match self.step {
+                    true => 1,
+                    false => self.world.accumulate(self.dt),
+                };
                 self.step = false;
-                self.solver.update(self.dt);
-                self.collisions = self
-                    .solver
-                    .get_polygons()
-                    .iter()
-                    .flat_map(|p| &p.collisions)
-                    .cloned()
-                    .collect();
-                if !self.collisions.is_empty() && self.pause_on_collision {
-                    self.collision_phase = CollisionPhase::Points;
-                    self.collision_index = 0;
-                    self.pause = true;
+                for _ in 0..steps {
+                    self.fixed_step();
+                    if self.pause {
+                        break;
+                    }
                 }
             } else {
                 let collision = self.collisions.get(self.collision_index);
@@ -691,7 +848,255 @@ impl Testbed {
         }
     }
 
+    /// One fixed step of the world, with the replayed and dragging actions
+    /// that belong to it.
+    fn fixed_step(&mut self) {
+        let frame = self.frame;
+        let actions = self
+            .replayer
+            .as_mut()
+            .map(|replayer| replayer.actions_for(frame))
+            .unwrap_or_default();
+        for action in actions {
+            self.apply(action);
+        }
+        if let Some((_, connector)) = self.grabbed {
+            self.apply(Action::MoveConnectorEnd {
+                connector,
+                pos: self.mouse_pos,
+            });
+        }
+        if let Some(replayer) = &self.replayer {
+            if replayer.is_finished() {
+                println!("Replay finished at frame {}", self.frame);
+                self.replayer = None;
+            }
+        }
+
+        self.world.step();
+        self.frame += 1;
+        self.push_history();
+        for event in self.world.contact_events() {
+            if event.phase == ContactPhase::Persist {
+                continue;
+            }
+            if self.contact_log.len() == CONTACT_LOG_LEN {
+                self.contact_log.pop_front();
+            }
+            self.contact_log.push_back(*event);
+        }
+        self.springs_broken += self.world.spring_breaks().len();
+        self.collisions = self
+            .world
+            .get_polygons()
+            .iter()
+            .flat_map(|p| &p.collisions)
+            .cloned()
+            .collect();
+        if !self.collisions.is_empty() && self.pause_on_collision {
+            self.collision_phase = CollisionPhase::Points;
+            self.collision_index = 0;
+            self.pause = true;
+        }
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            radius: self.radius,
+            point_count: self.point_count,
+            stiffness: self.stiffness,
+            pressure: self.pressure,
+            permanence_threshold: self.permanence_threshold,
+        }
+    }
+
+    /// Applies a change to the world, recording it when a recording is running.
+    fn apply(&mut self, action: Action) {
+        if let Err(e) = action.apply(&mut self.world) {
+            println!("Error applying {:?}: {}", action, e);
+            return;
+        }
+        match &action {
+            Action::AddPolygon(shape) => self.polygon_shapes.push(shape.clone()),
+            Action::Reset { .. } => {
+                self.polygon_shapes.clear();
+                self.grabbed = None;
+                self.selected = None;
+            }
+            Action::LoadScene(scene) => {
+                self.polygon_shapes = scene.polygons.iter().map(|p| p.shape.clone()).collect();
+                self.grabbed = None;
+                self.selected = None;
+            }
+            Action::Remove { body } => {
+                if let BodyRef::Polygon { index } = *body {
+                    if index < self.polygon_shapes.len() {
+                        self.polygon_shapes.swap_remove(index);
+                    }
+                }
+                // The last body of its list has moved into its place
+                self.release_grab();
+                self.selected = None;
+            }
+            Action::Settings(settings) => {
+                self.radius = settings.radius;
+                self.point_count = settings.point_count;
+                self.stiffness = settings.stiffness;
+                self.pressure = settings.pressure;
+                self.permanence_threshold = settings.permanence_threshold;
+                self.last_settings = settings.clone();
+            }
+            _ => {}
+        }
+        self.record(action);
+    }
+
+    fn record(&mut self, action: Action) {
+        if let Some(recording) = &mut self.recording {
+            recording.record(self.frame, action);
+        }
+    }
+
+    fn add_particle(&mut self, pos: Vector2<f32>) -> ParticleId {
+        self.record(Action::AddParticle { pos });
+        self.world.add_particle(pos)
+    }
+
+    fn add_circle(&mut self, circle: CircleDesc) -> CircleId {
+        let id = self.world.add_circle(circle.circle());
+        self.record(Action::AddCircle(circle));
+        id
+    }
+
+    fn add_connector(&mut self, connector: Connector) -> Option<ConnectorId> {
+        match self.world.add_connector(&connector) {
+            Ok(id) => {
+                self.record(Action::AddConnector(connector));
+                Some(id)
+            }
+            Err(e) => {
+                println!("Error adding {:?}: {}", connector, e);
+                None
+            }
+        }
+    }
+
+    /// Takes away the grab spring, returning the point it held.
+    fn release_grab(&mut self) -> Option<PointRef> {
+        let (point, connector) = self.grabbed.take()?;
+        // Removing the grabbed body takes its connectors with it
+        if self.world.connectors().any(|(id, _)| id == connector) {
+            self.apply(Action::RemoveConnector { connector });
+        }
+        Some(point)
+    }
+
+    fn reset(&mut self) {
+        self.apply(Action::Reset {
+            gravity: self.gravity,
+            bounds: SceneBounds {
+                pos: Vector2::zeros(),
+                size: Vector2::new(screen_width(), screen_height()),
+            },
+        });
+    }
+
+    fn load_scene(&mut self, path: &str) {
+        match Scene::load(path) {
+            Ok(scene) => self.apply(Action::LoadScene(scene)),
+            Err(e) => println!("Error loading scene {}: {}", path, e),
+        }
+    }
+
+    fn push_history(&mut self) {
+        if let Some(cursor) = self.history_cursor.take() {
+            // Stepping on from a rewound step drops the steps that followed it
+            self.history.truncate(cursor + 1);
+        }
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryEntry {
+            frame: self.frame,
+            polygon_shapes: self.polygon_shapes.clone(),
+            snapshot: self.world.snapshot(),
+            grab: self.grabbed.map(|(_, connector)| connector),
+        });
+    }
+
+    fn rewind_to(&mut self, index: usize) {
+        if let Some(entry) = self.history.get(index) {
+            self.world.restore(&entry.snapshot);
+            self.polygon_shapes = entry.polygon_shapes.clone();
+            self.frame = entry.frame;
+            self.history_cursor = Some(index);
+            self.pause = true;
+            self.grabbed = None;
+            self.selected = None;
+            self.replayer = None;
+            if let Some(recording) = &mut self.recording {
+                recording.truncate(entry.frame);
+            }
+            if let Some(connector) = entry.grab {
+                self.apply(Action::RemoveConnector { connector });
+            }
+        }
+    }
+
+    fn start_recording(&mut self) {
+        match Scene::from_world(&self.world, &self.polygon_shapes) {
+            Ok(scene) => {
+                self.frame = 0;
+                self.history.clear();
+                self.history_cursor = None;
+                self.recording = Some(Recording::new(&self.world, scene));
+                self.apply(Action::Settings(self.settings()));
+            }
+            Err(e) => println!("Error starting recording: {}", e),
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            match recording.save(&self.replay_path) {
+                Ok(()) => println!("Saved replay to {}", self.replay_path),
+                Err(e) => println!("Error saving replay {}: {}", self.replay_path, e),
+            }
+        }
+    }
+
+    fn start_replay(&mut self) {
+        match Recording::load(&self.replay_path) {
+            Ok(recording) => {
+                self.recording = None;
+                self.frame = 0;
+                self.history.clear();
+                self.history_cursor = None;
+                self.pause = false;
+                self.replayer = Some(Replayer::new(recording));
+            }
+            Err(e) => println!("Error loading replay {}: {}", self.replay_path, e),
+        }
+    }
+
+    fn save_scene(&self, path: &str) {
+        let result =
+            Scene::from_world(&self.world, &self.polygon_shapes).and_then(|scene| scene.save(path));
+        if let Err(e) = result {
+            println!("Error saving scene {}: {}", path, e);
+        }
+    }
+
     fn handle_input(&mut self) {
+        match self.tool {
+            Tool::Spawn => {}
+            Tool::Grab => return self.input_grab(),
+            Tool::Select => return self.input_select(),
+            Tool::Erase => return self.input_erase(),
+            Tool::Ray => return self.input_ray(),
+            Tool::Joint => return self.input_joint(),
+            Tool::Connect => return self.input_connect(),
+        }
         match self.spawn_mode {
             SpawnMode::Single => self.input_single(),
             SpawnMode::Grid => self.input_grid(),
@@ -700,75 +1105,221 @@ impl Testbed {
         }
     }
 
+    fn input_grab(&mut self) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.release_grab();
+            self.grabbed =
+                pick::nearest_point(&self.world, self.mouse_pos, self.radius).and_then(|point| {
+                    let connector = Connector {
+                        a: self.world.endpoint(point)?,
+                        b: Endpoint::World {
+                            pos: self.mouse_pos,
+                        },
+                        kind: ConnectorKind::Spring {
+                            rest_length: 0.0,
+                            stiffness: self.grab_stiffness,
+                        },
+                    };
+                    Some((point, self.add_connector(connector)?))
+                });
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            if let Some(point) = self.release_grab() {
+                // Let go with the velocity the mouse had, which moved this
+                // far over the whole frame
+                let velocity = self
+                    .world
+                    .timestep()
+                    .per_update(self.mouse_pos - self.prev_mouse_pos, self.dt);
+                self.apply(Action::Throw { point, velocity });
+            }
+        }
+    }
+
+    fn input_select(&mut self) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.selected = pick::pick_body(&self.world, self.mouse_pos, self.radius);
+        }
+    }
+
+    fn input_erase(&mut self) {
+        if is_mouse_button_down(MouseButton::Left) {
+            if let Some(body) = pick::pick_body(&self.world, self.mouse_pos, self.radius) {
+                self.apply(Action::Remove { body });
+            }
+        }
+    }
+
+    fn input_ray(&mut self) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.ray = Some((self.mouse_pos, self.mouse_pos));
+        }
+        if is_mouse_button_down(MouseButton::Left) {
+            if let Some(ray) = self.ray.as_mut() {
+                ray.1 = self.mouse_pos;
+            }
+        }
+    }
+
+    /// First click picks the first anchor, second click the second one.
+    /// Clicking outside every polygon anchors to the world.
+    fn input_joint(&mut self) {
+        if !is_mouse_button_pressed(MouseButton::Left) {
+            return;
+        }
+        let polygon =
+            self.world
+                .bodies_at(self.mouse_pos)
+                .into_iter()
+                .find_map(|body| match body {
+                    BodyRef::Polygon { index } => self.world.polygon_id(index),
+                    _ => None,
+                });
+        let anchor = match polygon {
+            Some(polygon) => Anchor::Polygon {
+                polygon,
+                pos: self.mouse_pos,
+            },
+            None => Anchor::World {
+                pos: self.mouse_pos,
+            },
+        };
+        let Some(a) = self.joint_start.take() else {
+            self.joint_start = Some(anchor);
+            return;
+        };
+        let anchor_pos = |anchor: &Anchor| match *anchor {
+            Anchor::Polygon { pos, .. } | Anchor::World { pos } => pos,
+        };
+        let kind = self.joint_type.kind(anchor_pos(&a), anchor_pos(&anchor));
+        self.apply(Action::AddJoint(JointDesc { a, b: anchor, kind }));
+    }
+
+    /// Ties the point clicked first to the point clicked second, or to the
+    /// world where no point is in reach. Links keep the distance the ends
+    /// had, springs rest at it.
+    fn input_connect(&mut self) {
+        if !is_mouse_button_pressed(MouseButton::Left) {
+            return;
+        }
+        let endpoint = pick::nearest_point(&self.world, self.mouse_pos, self.radius)
+            .and_then(|point| self.world.endpoint(point));
+        let endpoint = endpoint.unwrap_or(Endpoint::World {
+            pos: self.mouse_pos,
+        });
+        let Some(a) = self.connect_start.take() else {
+            self.connect_start = Some(endpoint);
+            return;
+        };
+        let connector = Connector {
+            a,
+            b: endpoint,
+            kind: ConnectorKind::Link {
+                target_distance: 0.0,
+            },
+        };
+        let Some((pos_a, pos_b)) = self.world.connector_ends(&connector) else {
+            return;
+        };
+        let distance = (pos_b - pos_a).magnitude();
+        let kind = match self.connect_spring {
+            true => ConnectorKind::Spring {
+                rest_length: distance,
+                stiffness: self.stiffness,
+            },
+            false => ConnectorKind::Link {
+                target_distance: distance,
+            },
+        };
+        self.apply(Action::AddConnector(Connector { kind, ..connector }));
+    }
+
     fn input_single(&mut self) {
         let should_spawn = is_mouse_button_pressed(MouseButton::Left);
         match self.spawn_type {
             SpawnType::Particle => {
                 self.overlay_particle();
                 if should_spawn {
-                    self.solver
-                        .add_particle(self.mouse_pos + Vector2::new(self.radius, self.radius));
-                    self.solver.add_particle(self.mouse_pos);
-                    self.solver
-                        .add_particle(self.mouse_pos + Vector2::new(0.0, self.radius));
-                    let length = self.solver.get_particle_len();
-                    self.solver.add_particle_spring(Spring {
-                        particle_a: length - 3,
-                        particle_b: length - 2,
+                    let a =
+                        self.add_particle(self.mouse_pos + Vector2::new(self.radius, self.radius));
+                    let b = self.add_particle(self.mouse_pos);
+                    let c = self.add_particle(self.mouse_pos + Vector2::new(0.0, self.radius));
+                    self.apply(Action::AddSpring(Spring {
+                        particle_a: a,
+                        particle_b: b,
                         rest_length: self.radius,
                         stiffness: self.stiffness,
                         permanence_threshold: self.permanence_threshold,
-                    });
-                    self.solver.add_particle_spring(Spring {
-                        particle_a: length - 3,
-                        particle_b: length - 1,
+                    }));
+                    self.apply(Action::AddSpring(Spring {
+                        particle_a: a,
+                        particle_b: c,
                         rest_length: self.radius,
                         stiffness: self.stiffness,
                         permanence_threshold: self.permanence_threshold,
-                    });
-                    self.solver.add_particle_spring(Spring {
-                        particle_a: length - 2,
-                        particle_b: length - 1,
+                    }));
+                    self.apply(Action::AddSpring(Spring {
+                        particle_a: b,
+                        particle_b: c,
                         rest_length: (self.radius * self.radius + self.radius * self.radius).sqrt(),
                         stiffness: self.stiffness,
                         permanence_threshold: self.permanence_threshold,
-                    });
+                    }));
                 }
             }
             SpawnType::Circle => {
                 self.overlay_circle();
                 if should_spawn {
-                    self.solver.add_circle(Circle {
-                        point: Particle::new(self.mouse_pos),
+                    self.apply(Action::AddCircle(CircleDesc {
+                        pos: self.mouse_pos,
+                        prev_pos: None,
                         radius: self.radius,
-                    });
+                    }));
                 }
             }
             SpawnType::Polygon => {
                 self.overlay_circle_polygon(Vector2::zeros());
                 if should_spawn {
-                    self.solver.add_polygon(Polygon::circle(
-                        self.radius,
-                        self.mouse_pos,
-                        self.point_count,
-                        false,
-                        self.stiffness,
-                        self.permanence_threshold,
-                    ));
+                    self.apply(Action::AddPolygon(PolygonShape::Circle {
+                        radius: self.radius,
+                        pos: self.mouse_pos,
+                        point_count: self.point_count,
+                        fixed: false,
+                        stiffness: self.stiffness,
+                        permanence_threshold: self.permanence_threshold,
+                    }));
                 }
             }
             SpawnType::PressurePolygon => {
                 self.overlay_circle_polygon(Vector2::zeros());
                 if should_spawn {
                     println!("pressure polygon: {}", self.pressure);
-                    self.solver.add_polygon(Polygon::pressure_circle(
-                        self.radius,
-                        self.mouse_pos,
-                        self.point_count,
-                        false,
-                        self.stiffness,
-                        self.pressure,
-                    ));
+                    self.apply(Action::AddPolygon(PolygonShape::PressureCircle {
+                        radius: self.radius,
+                        pos: self.mouse_pos,
+                        point_count: self.point_count,
+                        fixed: false,
+                        stiffness: self.stiffness,
+                        pressure: self.pressure,
+                    }));
+                }
+            }
+            SpawnType::Outline => {
+                // Left click adds a vertex, right click closes the outline
+                if should_spawn {
+                    self.outline.push(self.mouse_pos);
+                }
+                if is_mouse_button_pressed(MouseButton::Right) {
+                    let shape = PolygonShape::Outline {
+                        points: std::mem::take(&mut self.outline),
+                        fixed: false,
+                        stiffness: self.stiffness,
+                        permanence_threshold: self.permanence_threshold,
+                    };
+                    match shape.check() {
+                        Ok(()) => self.apply(Action::AddPolygon(shape)),
+                        Err(error) => println!("Not spawning outline: {}", error),
+                    }
                 }
             }
             SpawnType::Static => {
@@ -777,10 +1328,12 @@ impl Testbed {
                     Vector2::new(self.radius, self.radius),
                 ));
                 if should_spawn {
-                    self.solver.add_static_line((
-                        self.mouse_pos,
-                        self.mouse_pos + Vector2::new(self.radius, self.radius),
-                    ));
+                    self.apply(Action::AddStaticLine {
+                        line: [
+                            self.mouse_pos,
+                            self.mouse_pos + Vector2::new(self.radius, self.radius),
+                        ],
+                    });
                 }
             }
         }
@@ -793,7 +1346,7 @@ impl Testbed {
                 self.overlay_particle_grid();
                 if should_spawn {
                     spawn_particle_array(
-                        &mut self.solver,
+                        self,
                         self.mouse_pos,
                         Vector2::new(self.point_count as u32, self.point_count as u32),
                         self.radius,
@@ -806,7 +1359,7 @@ impl Testbed {
                 self.overlay_circle_grid();
                 if should_spawn {
                     spawn_circle_array(
-                        &mut self.solver,
+                        self,
                         self.mouse_pos,
                         Vector2::new(self.point_count as u32, self.point_count as u32),
                         self.radius * 2.0,
@@ -830,14 +1383,14 @@ impl Testbed {
                                 x as f32 * self.radius * 2.2,
                                 y as f32 * self.radius * 2.2,
                             );
-                            self.solver.add_polygon(Polygon::circle(
-                                self.radius,
-                                self.mouse_pos + offset,
-                                self.point_count,
-                                false,
-                                self.stiffness,
-                                self.permanence_threshold,
-                            ));
+                            self.apply(Action::AddPolygon(PolygonShape::Circle {
+                                radius: self.radius,
+                                pos: self.mouse_pos + offset,
+                                point_count: self.point_count,
+                                fixed: false,
+                                stiffness: self.stiffness,
+                                permanence_threshold: self.permanence_threshold,
+                            }));
                         }
                     }
                 }
@@ -858,14 +1411,14 @@ impl Testbed {
                                 x as f32 * self.radius * 2.2,
                                 y as f32 * self.radius * 2.2,
                             );
-                            self.solver.add_polygon(Polygon::pressure_circle(
-                                self.radius,
-                                self.mouse_pos + offset,
-                                self.point_count,
-                                false,
-                                self.stiffness,
-                                self.pressure,
-                            ));
+                            self.apply(Action::AddPolygon(PolygonShape::PressureCircle {
+                                radius: self.radius,
+                                pos: self.mouse_pos + offset,
+                                point_count: self.point_count,
+                                fixed: false,
+                                stiffness: self.stiffness,
+                                pressure: self.pressure,
+                            }));
                         }
                     }
                 }
@@ -880,47 +1433,55 @@ impl Testbed {
             SpawnType::Particle => {
                 self.overlay_last_particle();
                 if should_spawn {
-                    self.solver.add_particle(self.mouse_pos);
-                    let length = self.solver.get_particle_len();
-                    if length < 2 {
+                    let previous = self
+                        .world
+                        .get_particle_len()
+                        .checked_sub(1)
+                        .and_then(|index| self.world.particle_id(index));
+                    let particle = self.add_particle(self.mouse_pos);
+                    let Some(previous) = previous else {
                         return;
-                    }
-                    if let Some(particle) = self.solver.get_particle(length - 2) {
+                    };
+                    if let Ok(previous_particle) = self.world.particle(previous) {
+                        let target_distance = (self.mouse_pos - previous_particle.pos).magnitude();
                         // self.solver.add_particle_spring(Spring {
                         //     particle_a: length - 2,
                         //     particle_b: length - 1,
-                        //     rest_length: (self.mouse_pos - particle.pos).magnitude(),
+                        //     rest_length: target_distance,
                         //     stiffness: 1.0,
                         // });
-                        self.solver.add_particle_link(ParticleLink {
-                            link: Link {
-                                particle_a: length - 2,
-                                particle_b: length - 1,
-                                target_distance: (self.mouse_pos - particle.pos).magnitude(),
-                            },
-                        });
+                        self.apply(Action::AddParticleLink(ParticleLink {
+                            particle_a: previous,
+                            particle_b: particle,
+                            target_distance,
+                        }));
                     }
                 }
             }
             SpawnType::Circle => {
                 self.overlay_last_circle();
                 if should_spawn {
-                    self.solver.add_circle(Circle {
-                        point: Particle::new(self.mouse_pos),
+                    let previous = self
+                        .world
+                        .get_circles_len()
+                        .checked_sub(1)
+                        .and_then(|index| self.world.circle_id(index));
+                    let circle = self.add_circle(CircleDesc {
+                        pos: self.mouse_pos,
+                        prev_pos: None,
                         radius: self.radius,
                     });
-                    let length = self.solver.get_circles_len();
-                    if length < 2 {
+                    let Some(previous) = previous else {
                         return;
-                    }
-                    if let Some(particle) = self.solver.get_circle(length - 2) {
-                        self.solver.add_circle_link(CircleLink {
-                            link: Link {
-                                particle_a: length - 2,
-                                particle_b: length - 1,
-                                target_distance: (self.mouse_pos - particle.point.pos).magnitude(),
-                            },
-                        });
+                    };
+                    if let Ok(previous_circle) = self.world.circle(previous) {
+                        let target_distance =
+                            (self.mouse_pos - previous_circle.point.pos).magnitude();
+                        self.apply(Action::AddCircleLink(CircleLink {
+                            circle_a: previous,
+                            circle_b: circle,
+                            target_distance,
+                        }));
                     }
                 }
             }
@@ -934,16 +1495,19 @@ impl Testbed {
             SpawnType::Particle => {
                 self.overlay_particle();
                 if should_spawn {
-                    self.solver.add_particle(self.mouse_pos);
+                    self.apply(Action::AddParticle {
+                        pos: self.mouse_pos,
+                    });
                 }
             }
             SpawnType::Circle => {
                 self.overlay_circle();
                 if should_spawn {
-                    self.solver.add_circle(Circle {
-                        point: Particle::new(self.mouse_pos),
+                    self.apply(Action::AddCircle(CircleDesc {
+                        pos: self.mouse_pos,
+                        prev_pos: None,
                         radius: self.radius,
-                    });
+                    }));
                 }
             }
             _ => {}
@@ -975,11 +1539,11 @@ impl Testbed {
 
     fn overlay_last_particle(&mut self) {
         self.points_vec.push(Vector2::new(0.0, 0.0));
-        let length = self.solver.get_particle_len();
+        let length = self.world.get_particle_len();
         if length < 1 {
             return;
         }
-        if let Some(particle) = self.solver.get_particle(length - 1) {
+        if let Some(particle) = self.world.get_particle(length - 1) {
             self.points_vec.push(particle.pos - self.mouse_pos);
             self.links_vec.push(Vector2::new(0, 1));
         }
@@ -987,11 +1551,11 @@ impl Testbed {
 
     fn overlay_last_circle(&mut self) {
         self.circles_vec.push(Vector2::new(0.0, 0.0));
-        let length = self.solver.get_circles_len();
+        let length = self.world.get_circles_len();
         if length < 1 {
             return;
         }
-        if let Some(particle) = self.solver.get_circle(length - 1) {
+        if let Some(particle) = self.world.get_circle(length - 1) {
             self.circles_vec.push(particle.point.pos - self.mouse_pos);
             self.links_vec.push(Vector2::new(0, 1));
         }
@@ -1061,17 +1625,20 @@ impl Testbed {
     }
 
     fn draw(&mut self) {
+        // Bodies are drawn between their last two steps, see World::alpha
+        let world = &self.world;
         // Circles
-        let circles = self.solver.get_circles();
+        let circles = self.world.get_circles();
         // Draw circles
         for circle in circles.iter() {
-            draw_circle(circle.point.pos.x, circle.point.pos.y, circle.radius, BLUE);
+            let pos = world.interpolated(&circle.point);
+            draw_circle(pos.x, pos.y, circle.radius, BLUE);
         }
         // Draw circle links
-        let circles = self.solver.get_circles();
-        for link in self.solver.get_circle_links().iter() {
-            let particle_a = circles[link.link.particle_a].point.pos;
-            let particle_b = circles[link.link.particle_b].point.pos;
+        let circles = self.world.get_circles();
+        for link in self.world.get_circle_links().iter() {
+            let particle_a = world.interpolated(&circles[link.link.particle_a].point);
+            let particle_b = world.interpolated(&circles[link.link.particle_b].point);
             draw_line(
                 particle_a.x,
                 particle_a.y,
@@ -1083,15 +1650,16 @@ impl Testbed {
         }
 
         // Particles
-        let particles = self.solver.get_particles();
+        let particles = self.world.get_particles();
         // Draw particles
         for particle in particles.iter() {
-            draw_circle(particle.pos.x, particle.pos.y, 3.0, GREEN);
+            let pos = world.interpolated(particle);
+            draw_circle(pos.x, pos.y, 3.0, GREEN);
         }
         // Draw particle links
-        for link in self.solver.get_particle_links().iter() {
-            let particle_a = particles[link.link.particle_a].pos;
-            let particle_b = particles[link.link.particle_b].pos;
+        for link in self.world.get_particle_links().iter() {
+            let particle_a = world.interpolated(&particles[link.link.particle_a]);
+            let particle_b = world.interpolated(&particles[link.link.particle_b]);
             draw_line(
                 particle_a.x,
                 particle_a.y,
@@ -1101,39 +1669,47 @@ impl Testbed {
                 WHITE,
             );
         }
-        // Draw spring links
-        for link in self.solver.get_particle_springs().iter() {
-            let particle_a = particles[link.particle_a].pos;
-            let particle_b = particles[link.particle_b].pos;
+        // Draw spring links, coloured by how close they are to breaking
+        let strain_limit = self.world.break_strain().unwrap_or(0.5);
+        for link in self.world.get_particle_springs().iter() {
+            let particle_a = world.interpolated(&particles[link.particle_a]);
+            let particle_b = world.interpolated(&particles[link.particle_b]);
+            let strain =
+                plasticity::strain((particle_b - particle_a).magnitude(), link.rest_length);
             draw_line(
                 particle_a.x,
                 particle_a.y,
                 particle_b.x,
                 particle_b.y,
                 1.0,
-                RED,
+                strain_color(strain, strain_limit),
             );
         }
 
         // Draw polygons
-        for (p, polygon) in self.solver.get_polygons().iter().enumerate() {
+        for (p, polygon) in self.world.get_polygons().iter().enumerate() {
+            let points: Vec<Vector2<f32>> = polygon
+                .particles
+                .iter()
+                .map(|particle| world.interpolated(particle))
+                .collect();
+            let center = polygon.center
+                + points
+                    .iter()
+                    .zip(polygon.particles.iter())
+                    .map(|(point, particle)| point - particle.pos)
+                    .sum::<Vector2<f32>>()
+                    / points.len().max(1) as f32;
             for i in 0..polygon.particles.len() {
-                let point_a = polygon.particles[i];
-                let point_b = polygon.particles[(i + 1) % polygon.particles.len()];
+                let point_a = points[i];
+                let point_b = points[(i + 1) % points.len()];
                 draw_triangle(
-                    Vec2::new(point_a.pos.x, point_a.pos.y),
-                    Vec2::new(point_b.pos.x, point_b.pos.y),
-                    Vec2::new(polygon.center.x, polygon.center.y),
+                    Vec2::new(point_a.x, point_a.y),
+                    Vec2::new(point_b.x, point_b.y),
+                    Vec2::new(center.x, center.y),
                     GRAY,
                 );
-                draw_line(
-                    point_a.pos.x,
-                    point_a.pos.y,
-                    point_b.pos.x,
-                    point_b.pos.y,
-                    3.0,
-                    BLACK,
-                );
+                draw_line(point_a.x, point_a.y, point_b.x, point_b.y, 3.0, BLACK);
                 //draw_text(&format!("{}", i), point_a.pos.x, point_a.pos.y, 20.0, BLACK);
             }
 
@@ -1164,10 +1740,104 @@ impl Testbed {
         }
 
         // Draw static lines
-        for line in self.solver.get_static_lines().iter() {
+        for line in self.world.get_static_lines().iter() {
             draw_line(line.0.x, line.0.y, line.1.x, line.1.y, 3.0, BLUE);
         }
 
+        // Draw joints
+        for (_, joint) in self.world.joints() {
+            if let Some((a, b)) = self.world.joint_anchors(joint) {
+                let color = match joint.kind() {
+                    JointKind::Revolute { .. } => PINK,
+                    JointKind::Prismatic { .. } => PURPLE,
+                    JointKind::Weld => ORANGE,
+                };
+                draw_line(a.x, a.y, b.x, b.y, 2.0, color);
+                draw_circle(a.x, a.y, 4.0, color);
+                draw_circle(b.x, b.y, 4.0, color);
+            }
+        }
+        if let Some(Anchor::Polygon { pos, .. } | Anchor::World { pos }) = self.joint_start {
+            draw_circle_lines(pos.x, pos.y, 6.0, 2.0, PINK);
+        }
+
+        // Draw connectors
+        for (_, connector) in self.world.connectors() {
+            if let Some((a, b)) = self.world.connector_ends(connector) {
+                let color = match connector.kind {
+                    ConnectorKind::Spring { .. } => RED,
+                    ConnectorKind::Link { .. } => GREEN,
+                };
+                draw_line(a.x, a.y, b.x, b.y, 1.0, color);
+            }
+        }
+        if let Some(Endpoint::World { pos }) = self.connect_start {
+            draw_circle_lines(pos.x, pos.y, 6.0, 2.0, GREEN);
+        }
+
+        // Draw the outline being spawned, closed through the mouse
+        if let Some(first) = self.outline.first() {
+            let mut points = self.outline.clone();
+            points.push(self.mouse_pos);
+            points.push(*first);
+            for pair in points.windows(2) {
+                draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, 1.0, WHITE);
+            }
+            for point in self.outline.iter() {
+                draw_circle(point.x, point.y, 3.0, WHITE);
+            }
+        }
+
+        // Draw selection
+        if let Some(selected) = self.selected {
+            self.draw_selected(selected);
+        }
+
+        // Draw ray hits
+        if let (Tool::Ray, Some((start, end))) = (&self.tool, self.ray) {
+            draw_line(start.x, start.y, end.x, end.y, 1.0, WHITE);
+            let hits = self
+                .world
+                .raycast_all(start, end - start, (end - start).magnitude());
+            for hit in hits.iter() {
+                let normal_end = hit.point + hit.normal * 20.0;
+                draw_line(
+                    hit.point.x,
+                    hit.point.y,
+                    normal_end.x,
+                    normal_end.y,
+                    2.0,
+                    YELLOW,
+                );
+                draw_circle(hit.point.x, hit.point.y, 4.0, RED);
+            }
+            if let Some(hit) = hits.first() {
+                draw_text(
+                    &format!("{:?} at {:.1}", hit.body, hit.distance),
+                    end.x + 10.0,
+                    end.y,
+                    20.0,
+                    WHITE,
+                );
+            }
+        }
+
+        // Draw grab spring
+        if let Some(particle) = self
+            .grabbed
+            .and_then(|(point, _)| pick::point_particle(&self.world, point))
+        {
+            draw_line(
+                particle.pos.x,
+                particle.pos.y,
+                self.mouse_pos.x,
+                self.mouse_pos.y,
+                2.0,
+                ORANGE,
+            );
+            draw_circle(particle.pos.x, particle.pos.y, 5.0, ORANGE);
+        }
+
         let overlay_color = Color::new(1.0, 0.0, 0.0, 0.5);
         // Draw overlay points
         for point in self.points_vec.iter() {
@@ -1224,7 +1894,7 @@ impl Testbed {
             let collision = self.collisions.get(self.collision_index);
             if let Some(collision) = collision {
                 self.collision_phase
-                    .draw(collision, self.solver.get_polygons());
+                    .draw(collision, self.world.get_polygons());
             } else {
                 self.collisions.clear();
                 self.collision_index = 0;
@@ -1233,6 +1903,297 @@ impl Testbed {
         }
     }
 
+    fn draw_selected(&self, selected: BodyRef) {
+        let color = ORANGE;
+        let particles = self.world.get_particles();
+        let circles = self.world.get_circles();
+        let segment = match selected {
+            BodyRef::Particle { index } => {
+                if let Some(particle) = particles.get(index) {
+                    draw_circle_lines(particle.pos.x, particle.pos.y, 6.0, 2.0, color);
+                }
+                None
+            }
+            BodyRef::Circle { index } => {
+                if let Some(circle) = circles.get(index) {
+                    draw_circle_lines(
+                        circle.point.pos.x,
+                        circle.point.pos.y,
+                        circle.radius,
+                        3.0,
+                        color,
+                    );
+                }
+                None
+            }
+            BodyRef::Polygon { index } => {
+                if let Some(polygon) = self.world.get_polygons().get(index) {
+                    let count = polygon.particles.len();
+                    for i in 0..count {
+                        let point_a = polygon.particles[i].pos;
+                        let point_b = polygon.particles[(i + 1) % count].pos;
+                        draw_line(point_a.x, point_a.y, point_b.x, point_b.y, 3.0, color);
+                    }
+                }
+                None
+            }
+            BodyRef::Spring { index } => {
+                self.world.get_particle_springs().get(index).map(|spring| {
+                    (
+                        particles[spring.particle_a].pos,
+                        particles[spring.particle_b].pos,
+                    )
+                })
+            }
+            BodyRef::ParticleLink { index } => {
+                self.world.get_particle_links().get(index).map(|link| {
+                    (
+                        particles[link.link.particle_a].pos,
+                        particles[link.link.particle_b].pos,
+                    )
+                })
+            }
+            BodyRef::CircleLink { index } => self.world.get_circle_links().get(index).map(|link| {
+                (
+                    circles[link.link.particle_a].point.pos,
+                    circles[link.link.particle_b].point.pos,
+                )
+            }),
+            BodyRef::StaticLine { index } => self.world.get_static_lines().get(index).copied(),
+        };
+        if let Some((point_a, point_b)) = segment {
+            draw_line(point_a.x, point_a.y, point_b.x, point_b.y, 4.0, color);
+        }
+    }
+
+    fn inspector_ui(&mut self, ui: &mut egui::Ui, selected: BodyRef) {
+        match selected {
+            BodyRef::Particle { index } => {
+                let Some(particle) = self.world.get_particle(index) else {
+                    self.selected = None;
+                    return;
+                };
+                let mut pos = particle.pos;
+                ui.label(format!("Particle {}", index));
+                if vector_ui(ui, "Position", &mut pos) {
+                    self.apply(Action::MovePoint {
+                        point: PointRef::Particle { index },
+                        pos,
+                    });
+                }
+            }
+            BodyRef::Circle { index } => {
+                let Some(circle) = self.world.get_circle(index) else {
+                    self.selected = None;
+                    return;
+                };
+                let mut pos = circle.point.pos;
+                let mut radius = circle.radius;
+                ui.label(format!("Circle {}", index));
+                if vector_ui(ui, "Position", &mut pos) {
+                    self.apply(Action::MovePoint {
+                        point: PointRef::Circle { index },
+                        pos,
+                    });
+                }
+                if ui
+                    .add(egui::Slider::new(&mut radius, 1.0..=200.0).text("Radius"))
+                    .changed()
+                {
+                    self.apply(Action::SetCircleRadius { index, radius });
+                }
+            }
+            BodyRef::Polygon { index } => {
+                let Some(polygon) = self.world.get_polygons().get(index) else {
+                    self.selected = None;
+                    return;
+                };
+                let center = polygon.center;
+                let mut new_center = center;
+                ui.label(format!("Polygon {}", index));
+                ui.label(format!("Particles: {}", polygon.particles.len()));
+                ui.label(format!(
+                    "AABB: {}, {} size {}, {}",
+                    polygon.bounds.pos.x,
+                    polygon.bounds.pos.y,
+                    polygon.bounds.size.x,
+                    polygon.bounds.size.y
+                ));
+                ui.collapsing(format!("Collisions: {}", polygon.collisions.len()), |ui| {
+                    for collision in polygon.collisions.iter() {
+                        ui.label(format!(
+                            " Point: {}, {}",
+                            collision.point.pos.x, collision.point.pos.y
+                        ));
+                    }
+                });
+                if vector_ui(ui, "Center", &mut new_center) {
+                    self.apply(Action::MovePolygon {
+                        index,
+                        offset: new_center - center,
+                    });
+                }
+                if let Some(polygon) = self.world.polygon_id(index) {
+                    let motion = self.world.motion(polygon);
+                    ui.label(format!("Motion: {}", motion_name(motion)));
+                    if ui.button("Change motion").clicked() {
+                        self.apply(Action::SetMotion {
+                            polygon,
+                            motion: next_motion(motion),
+                        });
+                    }
+
+                    let current = self.world.shape_match(polygon);
+                    let mut enabled = current.is_some();
+                    let mut stiffness = current.unwrap_or(0.1);
+                    ui.checkbox(&mut enabled, "Shape matching");
+                    if enabled {
+                        ui.add(
+                            egui::Slider::new(&mut stiffness, 0.0..=1.0).text("Shape stiffness"),
+                        );
+                    }
+                    let stiffness = enabled.then_some(stiffness);
+                    if stiffness != current {
+                        self.apply(Action::SetShapeMatch { polygon, stiffness });
+                    }
+
+                    let current = self.world.pressure(polygon);
+                    let area = self.world.polygon_area(polygon).unwrap_or(0.0);
+                    match current {
+                        Some(pressure) => ui.label(format!(
+                            "Area: {:.0} of {:.0} at rest, pressure {:.1}",
+                            area,
+                            pressure.rest_area,
+                            pressure.pressure(area)
+                        )),
+                        None => ui.label(format!("Area: {:.0}", area)),
+                    };
+                    let model = current.map(|pressure| pressure.model);
+                    ui.label(format!("Pressure model: {}", pressure_model_name(model)));
+                    if ui.button("Change pressure model").clicked() {
+                        let pressure = next_pressure_model(model).map(|model| Pressure {
+                            model,
+                            rest_area: current.map_or(area, |pressure| pressure.rest_area),
+                        });
+                        self.apply(Action::SetPressure { polygon, pressure });
+                    }
+                    if let Some(mut pressure) = current {
+                        match &mut pressure.model {
+                            PressureModel::Constant { pressure: gas }
+                            | PressureModel::IdealGas { pressure: gas } => {
+                                ui.add(
+                                    egui::Slider::new(gas, 0.0..=10000.0)
+                                        .logarithmic(true)
+                                        .text("Gas pressure"),
+                                );
+                            }
+                            PressureModel::TargetArea { stiffness } => {
+                                ui.add(
+                                    egui::Slider::new(stiffness, 0.0..=1.0).text("Area stiffness"),
+                                );
+                            }
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("Inflate").clicked() {
+                                pressure.rest_area *= 1.1;
+                            }
+                            if ui.button("Deflate").clicked() {
+                                pressure.rest_area /= 1.1;
+                            }
+                        });
+                        if Some(pressure) != current {
+                            self.apply(Action::SetPressure {
+                                polygon,
+                                pressure: Some(pressure),
+                            });
+                        }
+                    }
+                }
+            }
+            BodyRef::Spring { index } => {
+                let Some(spring) = self.world.get_particle_springs().get(index) else {
+                    self.selected = None;
+                    return;
+                };
+                let mut spring = SpringDesc::from_spring(spring);
+                ui.label(format!(
+                    "Spring {}: particles {} - {}",
+                    index, spring.particle_a, spring.particle_b
+                ));
+                let mut changed = false;
+                changed |= ui
+                    .add(egui::Slider::new(&mut spring.stiffness, 0.0..=1000.0).text("Stiffness"))
+                    .changed();
+                changed |= ui
+                    .add(egui::DragValue::new(&mut spring.rest_length).prefix("Rest length: "))
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut spring.permanence_threshold, 0.0..=-1.0)
+                            .text("Permanence Threshold"),
+                    )
+                    .changed();
+                if changed {
+                    self.apply(Action::SetSpring { index, spring });
+                }
+            }
+            BodyRef::ParticleLink { index } => {
+                if let Some(link) = self.world.get_particle_links().get(index) {
+                    ui.label(format!(
+                        "Particle link {}: particles {} - {}, distance {}",
+                        index,
+                        link.link.particle_a,
+                        link.link.particle_b,
+                        link.link.target_distance
+                    ));
+                }
+            }
+            BodyRef::CircleLink { index } => {
+                if let Some(link) = self.world.get_circle_links().get(index) {
+                    ui.label(format!(
+                        "Circle link {}: circles {} - {}, distance {}",
+                        index,
+                        link.link.particle_a,
+                        link.link.particle_b,
+                        link.link.target_distance
+                    ));
+                }
+            }
+            BodyRef::StaticLine { index } => {
+                if let Some(line) = self.world.get_static_lines().get(index) {
+                    ui.label(format!(
+                        "Static line {}: {}, {} - {}, {}",
+                        index, line.0.x, line.0.y, line.1.x, line.1.y
+                    ));
+                }
+            }
+        }
+        if let Some(mut material) = self.world.material(selected) {
+            let label = match selected {
+                BodyRef::Particle { .. } => "Material (all free particles)",
+                _ => "Material",
+            };
+            ui.collapsing(label, |ui| {
+                if material_ui(ui, &mut material) {
+                    self.apply(Action::SetMaterial {
+                        body: selected,
+                        material,
+                    });
+                }
+            });
+        }
+        if let Some(mut filter) = self.world.collision_filter(selected) {
+            ui.collapsing("Collision filter", |ui| {
+                if filter_ui(ui, &mut filter) {
+                    self.apply(Action::SetCollisionFilter {
+                        body: selected,
+                        filter,
+                    });
+                }
+            });
+        }
+    }
+
     fn draw_ui(&mut self) {
         ui(|egui_ctx| {
             let hovered = egui::Window::new("Information")
@@ -1241,12 +2202,13 @@ impl Testbed {
                     ui.label(format!("ms: {}", self.dt));
                     ui.label(format!("Mouse: {} {}", self.mouse_pos.x, self.mouse_pos.y));
                     ui.label(format!("Radius: {}", self.radius));
-                    ui.label(format!("Particles: {}", self.solver.get_particle_len()));
-                    ui.label(format!("Circles: {}", self.solver.get_circles_len()));
+                    ui.label(format!("Frame: {}", self.frame));
+                    ui.label(format!("Particles: {}", self.world.get_particle_len()));
+                    ui.label(format!("Circles: {}", self.world.get_circles_len()));
                     ui.collapsing(
-                        format!("Polygons: {}", self.solver.get_polygons_len()),
+                        format!("Polygons: {}", self.world.get_polygons_len()),
                         |ui| {
-                            for (i, polygon) in self.solver.get_polygons().iter().enumerate() {
+                            for (i, polygon) in self.world.get_polygons().iter().enumerate() {
                                 ui.collapsing(
                                     format!(
                                         "Polygo ID: {}, points: {}",
@@ -1268,7 +2230,7 @@ impl Testbed {
 
                     ui.label(format!(
                         "Polygon intersections: {}",
-                        self.solver
+                        self.world
                             .get_polygons()
                             .iter()
                             .map(|p| p.collisions.len())
@@ -1284,7 +2246,39 @@ impl Testbed {
                         egui::Slider::new(&mut self.permanence_threshold, 0.0..=-1.0)
                             .text("Permanence Threshold"),
                     );
+                    let mut timestep = self.world.timestep();
+                    ui.add(egui::Slider::new(&mut timestep.step, 0.001..=0.05).text("Step"));
+                    ui.add(egui::Slider::new(&mut timestep.substeps, 1..=16).text("Substeps"));
+                    if timestep != self.world.timestep() {
+                        self.apply(Action::SetTimestep(timestep));
+                    }
 
+                    ui.label(format!("Tool: {}", self.tool.name()));
+                    if ui.button("Change tool").clicked() {
+                        self.tool.increase();
+                        self.release_grab();
+                        self.joint_start = None;
+                        self.connect_start = None;
+                    }
+                    if let Tool::Connect = self.tool {
+                        let connect_text = match self.connect_spring {
+                            true => "Connect with links",
+                            false => "Connect with springs",
+                        };
+                        if ui.button(connect_text).clicked() {
+                            self.connect_spring = !self.connect_spring;
+                        }
+                    }
+                    if let Tool::Joint = self.tool {
+                        ui.label(format!("Joint type: {}", self.joint_type.name()));
+                        if ui.button("Change joint type").clicked() {
+                            self.joint_type.increase();
+                        }
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.grab_stiffness, 0.0..=10000.0)
+                            .text("Grab stiffness"),
+                    );
                     ui.label(format!("Spawn mode: {}", self.spawn_mode.name()));
                     if ui.button("Change mode").clicked() {
                         self.spawn_mode.increase();
@@ -1292,50 +2286,21 @@ impl Testbed {
                     ui.label(format!("Test case: {}", self.test_case.name()));
                     if ui.button("Change case").clicked() {
                         self.test_case.increase();
-                        self.solver = Solver::new();
-                        self.solver.bounds.size = Vector2::new(screen_width(), screen_height());
-                        self.solver.gravity = self.gravity;
-                        match self.test_case {
-                            TestCase::Playground => {}
-                            TestCase::Triangle1 => {
-                                self.solver.add_polygon(Polygon::circle(
-                                    100.0,
-                                    Vector2::new(300.0, 300.0),
-                                    3,
-                                    false,
-                                    100.0,
-                                    self.permanence_threshold,
-                                ));
-                                self.solver.add_polygon(Polygon::circle(
-                                    100.0,
-                                    Vector2::new(300.0, 600.0),
-                                    3,
-                                    false,
-                                    100.0,
-                                    self.permanence_threshold,
-                                ));
-                            }
-                            TestCase::Triangle2 => {
-                                self.solver.add_polygon(Polygon::circle(
-                                    100.0,
-                                    Vector2::new(300.0, 300.0),
-                                    3,
-                                    true,
-                                    5.0,
-                                    self.permanence_threshold,
-                                ));
-                                self.solver.add_polygon(Polygon::circle(
-                                    20.0,
-                                    Vector2::new(600.0, 300.0),
-                                    3,
-                                    true,
-                                    25.0,
-                                    self.permanence_threshold,
-                                ));
-                            }
-                            _ => {}
+                        self.reset();
+                        if let Some(path) = self.test_case.scene_path() {
+                            self.load_scene(path);
                         }
                     }
+                    ui.text_edit_singleline(&mut self.scene_path);
+                    ui.horizontal(|ui| {
+                        if ui.button("Save scene").clicked() {
+                            self.save_scene(&self.scene_path);
+                        }
+                        if ui.button("Load scene").clicked() {
+                            let path = self.scene_path.clone();
+                            self.load_scene(&path);
+                        }
+                    });
                     if ui
                         .button(match self.draw_aabb {
                             true => "Don't draw bounding boxes",
@@ -1352,9 +2317,83 @@ impl Testbed {
                     if ui.button(pause_on_collision_text).clicked() {
                         self.pause_on_collision = !self.pause_on_collision;
                     }
+                    let mut count = self.world.swept_threads();
+                    ui.add(
+                        egui::Slider::new(&mut count, 1..=threads::available())
+                            .text("Swept pass threads"),
+                    );
+                    if count != self.world.swept_threads() {
+                        self.apply(Action::SetSweptThreads { count });
+                    }
+                    ui.label(format!(
+                        "Broad phase (swept and contact passes): {}",
+                        self.world.broad_phase().name()
+                    ));
+                    if ui.button("Change broad phase").clicked() {
+                        let mut broad_phase = self.world.broad_phase();
+                        broad_phase.increase();
+                        self.apply(Action::SetBroadPhase { broad_phase });
+                    }
+                    let swept_text = match self.world.swept_collisions() {
+                        true => "Disable swept collisions",
+                        false => "Enable swept collisions",
+                    };
+                    if ui.button(swept_text).clicked() {
+                        let enabled = !self.world.swept_collisions();
+                        self.apply(Action::SetSweptCollisions { enabled });
+                    }
+                    let contact_events_text = match self.world.contact_events_enabled() {
+                        true => "Disable contact events",
+                        false => "Enable contact events",
+                    };
+                    if ui.button(contact_events_text).clicked() {
+                        let enabled = !self.world.contact_events_enabled();
+                        self.world.set_contact_events(enabled);
+                        self.contact_log.clear();
+                    }
+                    ui.collapsing(
+                        format!("Contacts: {}", self.world.contacts().count()),
+                        |ui| {
+                            for event in self.contact_log.iter().rev() {
+                                ui.label(format!(
+                                    "{:?} {:?} - {:?}, impulse {:.1}",
+                                    event.phase, event.a, event.b, event.impulse
+                                ));
+                            }
+                        },
+                    );
+                    let current = self.world.break_strain();
+                    let mut breaking = current.is_some();
+                    let mut break_strain = current.unwrap_or(0.5);
+                    ui.checkbox(&mut breaking, "Springs break");
+                    if breaking {
+                        ui.add(
+                            egui::Slider::new(&mut break_strain, 0.0..=2.0).text("Break strain"),
+                        );
+                    }
+                    let break_strain = breaking.then_some(break_strain);
+                    if break_strain != current {
+                        self.apply(Action::SetBreakStrain { break_strain });
+                    }
+                    ui.label(format!("Springs broken: {}", self.springs_broken));
+                    let mut combine = self.world.combine_rules();
+                    ui.label(format!("Friction combine: {}", combine.friction.name()));
+                    if ui.button("Change friction combine").clicked() {
+                        combine.friction.increase();
+                        self.apply(Action::SetCombineRules(combine));
+                    }
+                    ui.label(format!(
+                        "Restitution combine: {}",
+                        combine.restitution.name()
+                    ));
+                    if ui.button("Change restitution combine").clicked() {
+                        combine.restitution.increase();
+                        self.apply(Action::SetCombineRules(combine));
+                    }
                     ui.label(format!("Spawn type: {}", self.spawn_type.name()));
                     if ui.button("Change type").clicked() {
                         self.spawn_type.increase();
+                        self.outline.clear();
                     }
                     if ui
                         .button(match self.pause {
@@ -1368,11 +2407,37 @@ impl Testbed {
                     if self.pause && ui.button("Step").clicked() {
                         self.step = true;
                     }
+                    if self.pause && !self.history.is_empty() {
+                        let last = self.history.len() - 1;
+                        let mut index = self.history_cursor.unwrap_or(last);
+                        if ui
+                            .add(egui::Slider::new(&mut index, 0..=last).text("History"))
+                            .changed()
+                        {
+                            self.rewind_to(index);
+                        }
+                        if ui.button("Step back").clicked() {
+                            self.rewind_to(index.saturating_sub(1));
+                        }
+                    }
                     if ui.button("Reset").clicked() {
-                        self.solver = Solver::new();
-                        self.solver.bounds.size = Vector2::new(screen_width(), screen_height());
-                        self.solver.gravity = self.gravity;
+                        self.reset();
                     }
+                    ui.text_edit_singleline(&mut self.replay_path);
+                    ui.horizontal(|ui| {
+                        if self.recording.is_some() {
+                            if ui.button("Stop recording").clicked() {
+                                self.stop_recording();
+                            }
+                        } else if ui.button("Record").clicked() {
+                            self.start_recording();
+                        }
+                        if self.replayer.is_some() {
+                            ui.label("Replaying");
+                        } else if ui.button("Replay").clicked() {
+                            self.start_replay();
+                        }
+                    });
                 })
                 .unwrap()
                 .response
@@ -1381,13 +2446,126 @@ impl Testbed {
                     x: self.mouse_pos.x,
                     y: self.mouse_pos.y,
                 });
-            self.ui_hovered = hovered;
+            let inspector_hovered = match self.selected {
+                Some(selected) => egui::Window::new("Inspector")
+                    .show(egui_ctx, |ui| self.inspector_ui(ui, selected))
+                    .map(|inner| {
+                        inner.response.rect.contains(Pos2 {
+                            x: self.mouse_pos.x,
+                            y: self.mouse_pos.y,
+                        })
+                    })
+                    .unwrap_or(false),
+                None => false,
+            };
+            self.ui_hovered = hovered || inspector_hovered;
         });
 
         egui_macroquad::draw();
     }
 }
 
+// Two drag values for a vector, true when either changed
+fn vector_ui(ui: &mut egui::Ui, label: &str, value: &mut Vector2<f32>) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let x = ui.add(egui::DragValue::new(&mut value.x)).changed();
+        let y = ui.add(egui::DragValue::new(&mut value.y)).changed();
+        x || y
+    })
+    .inner
+}
+
+fn pressure_model_name(model: Option<PressureModel>) -> &'static str {
+    match model {
+        None => "None",
+        Some(PressureModel::Constant { .. }) => "Constant",
+        Some(PressureModel::IdealGas { .. }) => "Ideal gas",
+        Some(PressureModel::TargetArea { .. }) => "Target area",
+    }
+}
+
+fn next_pressure_model(model: Option<PressureModel>) -> Option<PressureModel> {
+    match model {
+        None => Some(PressureModel::Constant { pressure: 500.0 }),
+        Some(PressureModel::Constant { .. }) => Some(PressureModel::IdealGas { pressure: 500.0 }),
+        Some(PressureModel::IdealGas { .. }) => Some(PressureModel::TargetArea { stiffness: 0.5 }),
+        Some(PressureModel::TargetArea { .. }) => None,
+    }
+}
+
+/// Green for a relaxed spring, turning red as it is stretched or
+/// compressed towards `limit`.
+fn strain_color(strain: f32, limit: f32) -> Color {
+    let t = (strain.abs() / limit.max(1e-6)).min(1.0);
+    Color::new(t, 1.0 - t, 0.0, 1.0)
+}
+
+fn motion_name(motion: Option<Motion>) -> &'static str {
+    match motion {
+        None => "Dynamic",
+        Some(Motion::Fixed) => "Fixed",
+        Some(Motion::Linear { .. }) => "Linear",
+        Some(Motion::Spin { .. }) => "Spin",
+        Some(Motion::Oscillate { .. }) => "Oscillate",
+        Some(Motion::Manual(_)) => "Manual",
+    }
+}
+
+/// The motions the inspector cycles through: a static body, a rotating
+/// paddle and a moving platform.
+fn next_motion(motion: Option<Motion>) -> Option<Motion> {
+    match motion {
+        None => Some(Motion::Fixed),
+        Some(Motion::Fixed) => Some(Motion::Spin {
+            angular_velocity: 1.0,
+        }),
+        Some(Motion::Spin { .. }) => Some(Motion::Oscillate {
+            amplitude: Vector2::new(200.0, 0.0),
+            period: 4.0,
+        }),
+        Some(_) => None,
+    }
+}
+
+fn material_ui(ui: &mut egui::Ui, material: &mut Material) -> bool {
+    let mut changed = false;
+    changed |= ui
+        .add(egui::Slider::new(&mut material.static_friction, 0.0..=2.0).text("Static friction"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut material.dynamic_friction, 0.0..=2.0).text("Dynamic friction"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut material.restitution, 0.0..=1.0).text("Restitution"))
+        .changed();
+    changed
+}
+
+/// Check boxes for the first `FILTER_LAYERS` layers of a filter, the rest
+/// are left as they are.
+const FILTER_LAYERS: u32 = 8;
+
+fn filter_ui(ui: &mut egui::Ui, filter: &mut CollisionFilter) -> bool {
+    let mut changed = false;
+    for (label, bits) in [("Layers", &mut filter.layers), ("Mask", &mut filter.mask)] {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            for layer in 0..FILTER_LAYERS {
+                let mut on = *bits & (1 << layer) != 0;
+                if ui.checkbox(&mut on, format!("{}", layer + 1)).changed() {
+                    *bits ^= 1 << layer;
+                    changed = true;
+                }
+            }
+        });
+    }
+    changed |= ui
+        .add(egui::DragValue::new(&mut filter.group).prefix("Group: "))
+        .changed();
+    changed
+}
+
 #[macroquad::main("BasicShapes")]
 async fn main() {
     request_new_screen_size(1920.0, 1080.0);
@@ -0,0 +1,212 @@
+use crate::contact::Contact;
+use crate::pick::{point_particle, point_particle_mut, BodyRef, PointRef};
+use crate::world::BodyTable;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// Surface properties of a polygon, circle or static line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    /// Sliding only starts once the tangential speed is more than this many
+    /// times the normal load.
+    pub static_friction: f32,
+    /// How much of the normal load is taken off the tangential speed while
+    /// sliding.
+    pub dynamic_friction: f32,
+    /// Fraction of the approach speed kept when bouncing off, 0 to 1.
+    pub restitution: f32,
+}
+
+/// How the values of the two materials in a contact are turned into one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Combine {
+    #[default]
+    Average,
+    Min,
+    Max,
+    Multiply,
+}
+
+impl Combine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Combine::Average => "Average",
+            Combine::Min => "Min",
+            Combine::Max => "Max",
+            Combine::Multiply => "Multiply",
+        }
+    }
+
+    pub fn increase(&mut self) {
+        *self = match self {
+            Combine::Average => Combine::Min,
+            Combine::Min => Combine::Max,
+            Combine::Max => Combine::Multiply,
+            Combine::Multiply => Combine::Average,
+        }
+    }
+
+    pub fn apply(&self, a: f32, b: f32) -> f32 {
+        match self {
+            Combine::Average => (a + b) / 2.0,
+            Combine::Min => a.min(b),
+            Combine::Max => a.max(b),
+            Combine::Multiply => a * b,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CombineRules {
+    pub friction: Combine,
+    pub restitution: Combine,
+}
+
+impl CombineRules {
+    pub fn combine(&self, a: &Material, b: &Material) -> Material {
+        Material {
+            static_friction: self.friction.apply(a.static_friction, b.static_friction),
+            dynamic_friction: self.friction.apply(a.dynamic_friction, b.dynamic_friction),
+            restitution: self.restitution.apply(a.restitution, b.restitution),
+        }
+    }
+}
+
+/// Materials by the current index of each body. Free particles have no
+/// material of their own and use `default`.
+pub type MaterialTable = BodyTable<Material>;
+
+/// Every point's velocity at the start of a step, to tell afterwards how
+/// hard its contacts pushed it.
+pub struct Velocities {
+    particles: Vec<Vector2<f32>>,
+    circles: Vec<Vector2<f32>>,
+    polygons: Vec<Vec<Vector2<f32>>>,
+}
+
+impl Velocities {
+    pub fn capture(solver: &Solver) -> Self {
+        Self {
+            particles: solver
+                .get_particles()
+                .iter()
+                .map(|p| p.pos - p.prev_pos)
+                .collect(),
+            circles: solver
+                .get_circles()
+                .iter()
+                .map(|c| c.point.pos - c.point.prev_pos)
+                .collect(),
+            polygons: solver
+                .get_polygons()
+                .iter()
+                .map(|polygon| {
+                    polygon
+                        .particles
+                        .iter()
+                        .map(|p| p.pos - p.prev_pos)
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, point: PointRef) -> Option<Vector2<f32>> {
+        match point {
+            PointRef::Particle { index } => self.particles.get(index).copied(),
+            PointRef::Circle { index } => self.circles.get(index).copied(),
+            PointRef::PolygonVertex { polygon, vertex } => self
+                .polygons
+                .get(polygon)
+                .and_then(|vertices| vertices.get(vertex))
+                .copied(),
+        }
+    }
+}
+
+/// Applies friction and restitution to the points in `contacts` after a
+/// step of `dt` seconds. `before` holds the velocities from the start of
+/// the step.
+///
+/// The normal load is how much the step's collision response slowed the
+/// point along the contact normal, compared to where `before` and gravity
+/// alone would have taken it. Against polygons and static lines only the
+/// point side of a contact is changed, a polygon gets its own contacts
+/// with the points of the first. A circle touched by a particle or another
+/// circle has no contact of its own, so the change in relative velocity is
+/// split evenly between the two, equal and opposite.
+pub fn respond(
+    solver: &mut Solver,
+    contacts: &[Contact],
+    before: &Velocities,
+    table: &MaterialTable,
+    rules: &CombineRules,
+    dt: f32,
+) {
+    let gravity_step = solver.gravity * dt * dt;
+    // Approach speeds below this are gravity settling, not an impact
+    let resting = gravity_step.magnitude() * 2.0;
+
+    for contact in contacts {
+        let Some(before_point) = before.get(contact.point) else {
+            continue;
+        };
+        let Some(particle) = point_particle(solver, contact.point) else {
+            continue;
+        };
+        // The circle on the other side, which moves with the point
+        let other = match contact.other {
+            BodyRef::Circle { index } => Some(PointRef::Circle { index }),
+            _ => None,
+        };
+        let (surface, expected) = match other {
+            Some(other) => {
+                let (Some(other_particle), Some(before_other)) =
+                    (point_particle(solver, other), before.get(other))
+                else {
+                    continue;
+                };
+                let surface = other_particle.pos - other_particle.prev_pos;
+                (surface, (before_point - before_other).dot(&contact.normal))
+            }
+            None => (
+                contact.surface_velocity,
+                contact.expected_speed(before_point, gravity_step),
+            ),
+        };
+        let material = rules.combine(&table.point(contact.point), &table.body(contact.other));
+        let normal = contact.normal;
+
+        let velocity = particle.pos - particle.prev_pos - surface;
+        let normal_speed = velocity.dot(&normal);
+        let mut tangent = velocity - normal * normal_speed;
+        let load = (normal_speed - expected).max(0.0);
+
+        let tangent_speed = tangent.magnitude();
+        if tangent_speed <= material.static_friction * load {
+            tangent = Vector2::zeros();
+        } else if tangent_speed > 0.0 {
+            tangent *= (1.0 - material.dynamic_friction * load / tangent_speed).max(0.0);
+        }
+
+        let incoming = (-expected).max(0.0);
+        let mut normal_speed = normal_speed;
+        if incoming > resting {
+            normal_speed = normal_speed.max(material.restitution * incoming);
+        }
+
+        let change = normal * normal_speed + tangent - velocity;
+        let (point_share, other_share) = match other {
+            Some(_) => (change / 2.0, -change / 2.0),
+            None => (change, Vector2::zeros()),
+        };
+        if let Some(particle) = point_particle_mut(solver, contact.point) {
+            particle.prev_pos -= point_share;
+        }
+        if let Some(particle) = other.and_then(|other| point_particle_mut(solver, other)) {
+            particle.prev_pos -= other_share;
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use bendy2d::particle::Particle;
+use bendy2d::polygon::Polygon;
+use bendy2d::spring::Spring;
+use nalgebra::Vector2;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Why an outline can't be turned into a polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutlineError {
+    TooFewPoints,
+    /// Two edges cross, or a vertex lies on an edge it isn't part of.
+    SelfIntersecting,
+    /// The outline encloses no area.
+    Degenerate,
+}
+
+impl fmt::Display for OutlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutlineError::TooFewPoints => write!(f, "an outline needs at least 3 points"),
+            OutlineError::SelfIntersecting => write!(f, "the outline crosses itself"),
+            OutlineError::Degenerate => write!(f, "the outline encloses no area"),
+        }
+    }
+}
+
+impl std::error::Error for OutlineError {}
+
+/// Twice the signed area of the outline, positive when counter-clockwise in
+/// a y-up frame.
+pub fn signed_area(outline: &[Vector2<f32>]) -> f32 {
+    (0..outline.len())
+        .map(|i| outline[i].perp(&outline[(i + 1) % outline.len()]))
+        .sum()
+}
+
+fn segments_cross(a: [Vector2<f32>; 2], b: [Vector2<f32>; 2]) -> bool {
+    let side = |p: Vector2<f32>, q: Vector2<f32>, r: Vector2<f32>| (q - p).perp(&(r - p));
+    let (d1, d2) = (side(b[0], b[1], a[0]), side(b[0], b[1], a[1]));
+    let (d3, d4) = (side(a[0], a[1], b[0]), side(a[0], a[1], b[1]));
+    d1 * d2 <= 0.0 && d3 * d4 <= 0.0 && (d1 != 0.0 || d2 != 0.0)
+}
+
+/// Whether no two edges of the outline touch except neighbours at their
+/// shared vertex.
+pub fn is_simple(outline: &[Vector2<f32>]) -> bool {
+    let n = outline.len();
+    let edge = |i: usize| [outline[i], outline[(i + 1) % n]];
+    for i in 0..n {
+        for j in i + 1..n {
+            if j == i + 1 || (i == 0 && j == n - 1) {
+                continue;
+            }
+            if segments_cross(edge(i), edge(j)) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Splits a simple outline, concave or not and in either winding, into
+/// triangles of outline indices by ear clipping.
+pub fn triangulate(outline: &[Vector2<f32>]) -> Result<Vec<[usize; 3]>, OutlineError> {
+    if outline.len() < 3 {
+        return Err(OutlineError::TooFewPoints);
+    }
+    let area = signed_area(outline);
+    if area.abs() < 1e-6 {
+        return Err(OutlineError::Degenerate);
+    }
+    if !is_simple(outline) {
+        return Err(OutlineError::SelfIntersecting);
+    }
+
+    // Convex corners turn the same way as the whole outline
+    let winding = area.signum();
+    let convex = |a: usize, b: usize, c: usize| {
+        (outline[b] - outline[a]).perp(&(outline[c] - outline[b])) * winding > 0.0
+    };
+    let inside = |p: Vector2<f32>, [a, b, c]: [usize; 3]| {
+        let side =
+            |from: usize, to: usize| (outline[to] - outline[from]).perp(&(p - outline[from]));
+        side(a, b) * winding >= 0.0 && side(b, c) * winding >= 0.0 && side(c, a) * winding >= 0.0
+    };
+
+    let mut remaining: Vec<usize> = (0..outline.len()).collect();
+    let mut triangles = Vec::with_capacity(outline.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let triangle = [
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            ];
+            convex(triangle[0], triangle[1], triangle[2])
+                && remaining
+                    .iter()
+                    .filter(|index| !triangle.contains(index))
+                    .all(|index| !inside(outline[*index], triangle))
+        });
+        // A simple outline always has an ear, so this only happens when
+        // collinear points leave nothing but flat corners
+        let Some(ear) = ear else {
+            return Err(OutlineError::Degenerate);
+        };
+        triangles.push([
+            remaining[(ear + n - 1) % n],
+            remaining[ear],
+            remaining[(ear + 1) % n],
+        ]);
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    Ok(triangles)
+}
+
+/// One spring per distinct triangle edge, outline edges included, at the
+/// length it has in the outline.
+pub fn mesh_springs(
+    outline: &[Vector2<f32>],
+    triangles: &[[usize; 3]],
+    stiffness: f32,
+    permanence_threshold: f32,
+) -> Vec<Spring> {
+    let mut edges = BTreeSet::new();
+    for triangle in triangles.iter() {
+        for i in 0..3 {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            edges.insert((a.min(b), a.max(b)));
+        }
+    }
+    edges
+        .into_iter()
+        .map(|(a, b)| Spring {
+            particle_a: a,
+            particle_b: b,
+            rest_length: (outline[b] - outline[a]).magnitude(),
+            stiffness,
+            permanence_threshold,
+        })
+        .collect()
+}
+
+/// A soft polygon with the given outline, braced by springs along the edges
+/// of its triangulation.
+///
+/// The outline is reversed if needed to wind counter-clockwise, the way
+/// `Polygon::circle` and `Polygon::new_box` lay out their points, which the
+/// collision normals rely on.
+pub fn polygon(
+    outline: &[Vector2<f32>],
+    fixed: bool,
+    stiffness: f32,
+    permanence_threshold: f32,
+) -> Result<Polygon, OutlineError> {
+    let mut triangles = triangulate(outline)?;
+    let mut points = outline.to_vec();
+    if signed_area(outline) < 0.0 {
+        points.reverse();
+        let last = points.len() - 1;
+        for triangle in triangles.iter_mut() {
+            for index in triangle.iter_mut() {
+                *index = last - *index;
+            }
+        }
+    }
+
+    let particles = points.iter().map(|pos| Particle::new(*pos)).collect();
+    let springs = mesh_springs(&points, &triangles, stiffness, permanence_threshold);
+    Ok(Polygon::new(particles, springs, fixed))
+}
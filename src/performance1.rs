@@ -1,45 +1,19 @@
-use bendy2d::polygon::Polygon;
-use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::bench::{self, Stats};
+use bendy2d_testing::broad_phase;
+use bendy2d_testing::runner;
+use bendy2d_testing::scenarios;
 use macroquad::prelude::*;
-use nalgebra::Vector2;
 use std::time::Instant;
 
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
-
 #[macroquad::main("BasicShapes")]
 async fn main() {
-    let screen_size = Vector2::new(1920.0, 1080.0);
+    let screen_size = scenarios::screen_size();
     request_new_screen_size(screen_size.x, screen_size.y);
-    let scale = 30.0;
-
-    let mut solver = Solver::new();
-    solver.bounds = Bounds {
-        pos: Vector2::new(0.0, 0.0),
-        size: screen_size / scale,
-    };
-    solver.gravity = Vector2::new(0.0, 0.98);
 
-    let size = Vector2::new(32.0, 32.0);
-    let pos = Vector2::new(100.0, 100.0);
-    for x in 0..15 {
-        for y in 0..15 {
-            let index = Vector2::new(x as f32, y as f32);
-            solver.add_polygon(Polygon::new_box(
-                (pos + Vector2::new(index.x + index.x * size.x, index.y + index.y * size.y))
-                    / scale,
-                0.0,
-                size / scale,
-                4.0,
-                200.0,
-                false,
-                -1.0,
-            ));
-        }
-    }
+    let mut scenario = scenarios::performance1();
+    let scale = scenario.scale;
 
-    let sim_steps = 2000;
+    let sim_steps = scenario.steps;
     let mut latencies = Vec::<f32>::new();
     latencies.resize(sim_steps, 0.0);
     let clock = Instant::now();
@@ -57,12 +31,12 @@ async fn main() {
 
         let last_frame_time = clock.elapsed().as_secs_f32();
 
-        solver.update(1.0 / 60.0);
+        scenario.solver.update(scenario.dt);
 
         latencies[frame_count] = clock.elapsed().as_secs_f32() - last_frame_time;
         frame_count += 1;
 
-        for polygon in solver.get_polygons().iter() {
+        for polygon in scenario.solver.get_polygons().iter() {
             let particle_count = polygon.particles.len();
             for (i, point) in polygon.particles.iter().enumerate() {
                 draw_triangle(
@@ -82,19 +56,15 @@ async fn main() {
     for latency in latencies.iter() {
         println!("{}", latency);
     }
-
-    // Write out the positions to a CSV file
-    let mut file = File::create("performance1.csv");
-    if let Err(e) = file {
-        println!("Error creating file: {}", e);
-        return;
+    if let Some(stats) = Stats::from_latencies(&latencies) {
+        println!("{}", stats);
     }
-    let mut file = file.unwrap();
-
-    let mut data = String::new();
-    for latency in latencies.iter() {
-        data += &format!("{}\n", latency);
+    println!("{}", broad_phase::pair_diagnostics(&scenario.solver));
+    for (threads, stats) in bench::swept_thread_scaling(scenarios::performance1) {
+        println!("{} swept pass threads, solver single-threaded: {}", threads, stats);
     }
-    file.write_all(data.as_bytes())
+
+    // Write out the latencies to a CSV file
+    runner::write_latencies_csv("performance1.csv", &latencies)
         .expect("Failed to write to file");
 }
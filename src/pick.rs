@@ -0,0 +1,160 @@
+use bendy2d::common::is_point_in_polygon;
+use bendy2d::particle::Particle;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// A single simulated point: a free particle, the center of a circle or a
+/// vertex of a polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PointRef {
+    Particle { index: usize },
+    Circle { index: usize },
+    PolygonVertex { polygon: usize, vertex: usize },
+}
+
+impl PointRef {
+    /// The body the point belongs to.
+    pub fn body(&self) -> BodyRef {
+        match *self {
+            PointRef::Particle { index } => BodyRef::Particle { index },
+            PointRef::Circle { index } => BodyRef::Circle { index },
+            PointRef::PolygonVertex { polygon, .. } => BodyRef::Polygon { index: polygon },
+        }
+    }
+}
+
+/// The point closest to `pos` within `max_distance`. Circles count from
+/// their edge, so any point inside a circle picks it.
+pub fn nearest_point(solver: &Solver, pos: Vector2<f32>, max_distance: f32) -> Option<PointRef> {
+    let mut nearest = None;
+    let mut nearest_distance = max_distance;
+    let mut consider = |point: PointRef, distance: f32| {
+        if distance <= nearest_distance {
+            nearest_distance = distance;
+            nearest = Some(point);
+        }
+    };
+
+    for (index, particle) in solver.get_particles().iter().enumerate() {
+        consider(
+            PointRef::Particle { index },
+            (particle.pos - pos).magnitude(),
+        );
+    }
+    for (index, circle) in solver.get_circles().iter().enumerate() {
+        let distance = (circle.point.pos - pos).magnitude() - circle.radius;
+        consider(PointRef::Circle { index }, distance.max(0.0));
+    }
+    for (polygon, shape) in solver.get_polygons().iter().enumerate() {
+        for (vertex, particle) in shape.particles.iter().enumerate() {
+            consider(
+                PointRef::PolygonVertex { polygon, vertex },
+                (particle.pos - pos).magnitude(),
+            );
+        }
+    }
+
+    nearest
+}
+
+pub fn point_particle(solver: &Solver, point: PointRef) -> Option<&Particle> {
+    match point {
+        PointRef::Particle { index } => solver.get_particle(index),
+        PointRef::Circle { index } => solver.get_circle(index).map(|circle| &circle.point),
+        PointRef::PolygonVertex { polygon, vertex } => solver
+            .get_polygons()
+            .get(polygon)
+            .and_then(|polygon| polygon.particles.get(vertex)),
+    }
+}
+
+pub fn point_particle_mut(solver: &mut Solver, point: PointRef) -> Option<&mut Particle> {
+    match point {
+        PointRef::Particle { index } => solver.get_particle_mut(index),
+        PointRef::Circle { index } => solver.get_circle_mut(index).map(|circle| &mut circle.point),
+        PointRef::PolygonVertex { polygon, vertex } => solver
+            .get_polygon_mut(polygon)
+            .and_then(|polygon| polygon.particles.get_mut(vertex)),
+    }
+}
+
+/// Anything in the solver that can be selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BodyRef {
+    Particle { index: usize },
+    Circle { index: usize },
+    Polygon { index: usize },
+    Spring { index: usize },
+    ParticleLink { index: usize },
+    CircleLink { index: usize },
+    StaticLine { index: usize },
+}
+
+/// The body under `pos`. Points and circles win over the polygon they sit
+/// on, and polygons win over springs and lines within `max_distance`.
+pub fn pick_body(solver: &Solver, pos: Vector2<f32>, max_distance: f32) -> Option<BodyRef> {
+    match nearest_point(solver, pos, max_distance) {
+        Some(PointRef::Particle { index }) => return Some(BodyRef::Particle { index }),
+        Some(PointRef::Circle { index }) => return Some(BodyRef::Circle { index }),
+        _ => {}
+    }
+
+    for (index, polygon) in solver.get_polygons().iter().enumerate() {
+        let points: Vec<Vector2<f32>> = polygon.particles.iter().map(|p| p.pos).collect();
+        if is_point_in_polygon(&pos, &points).is_some() {
+            return Some(BodyRef::Polygon { index });
+        }
+    }
+
+    let mut nearest = None;
+    let mut nearest_distance = max_distance;
+    let mut consider = |body: BodyRef, a: Vector2<f32>, b: Vector2<f32>| {
+        let distance = distance_to_segment(pos, a, b);
+        if distance <= nearest_distance {
+            nearest_distance = distance;
+            nearest = Some(body);
+        }
+    };
+
+    let particles = solver.get_particles();
+    for (index, spring) in solver.get_particle_springs().iter().enumerate() {
+        consider(
+            BodyRef::Spring { index },
+            particles[spring.particle_a].pos,
+            particles[spring.particle_b].pos,
+        );
+    }
+    for (index, link) in solver.get_particle_links().iter().enumerate() {
+        consider(
+            BodyRef::ParticleLink { index },
+            particles[link.link.particle_a].pos,
+            particles[link.link.particle_b].pos,
+        );
+    }
+    let circles = solver.get_circles();
+    for (index, link) in solver.get_circle_links().iter().enumerate() {
+        consider(
+            BodyRef::CircleLink { index },
+            circles[link.link.particle_a].point.pos,
+            circles[link.link.particle_b].point.pos,
+        );
+    }
+    for (index, line) in solver.get_static_lines().iter().enumerate() {
+        consider(BodyRef::StaticLine { index }, line.0, line.1);
+    }
+
+    nearest
+}
+
+pub fn distance_to_segment(pos: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let line = b - a;
+    let length_squared = line.magnitude_squared();
+    if length_squared == 0.0 {
+        return (pos - a).magnitude();
+    }
+    let t = ((pos - a).dot(&line) / length_squared).clamp(0.0, 1.0);
+    (pos - (a + line * t)).magnitude()
+}
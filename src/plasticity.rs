@@ -0,0 +1,144 @@
+use crate::connector::Endpoint;
+use bendy2d::solver::Solver;
+use bendy2d::spring::Spring;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// A spring stretched past the breaking strain and removed during an
+/// update. Its ends are free particles or two vertices of one polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpringBreak {
+    pub a: Endpoint,
+    pub b: Endpoint,
+    /// Halfway between the ends when the spring broke.
+    pub pos: Vector2<f32>,
+    pub rest_length: f32,
+    pub length: f32,
+}
+
+pub type SpringBreakListener = Box<dyn FnMut(&SpringBreak)>;
+
+/// A spring taken out by the [`SolverPlasticity`] passes, by particle
+/// index, or by vertex index within `polygon` for a polygon spring.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Broken {
+    pub polygon: Option<usize>,
+    pub particle_a: usize,
+    pub particle_b: usize,
+    pub rest_length: f32,
+    pub length: f32,
+}
+
+/// How far a spring is stretched relative to its rest length, negative
+/// when compressed.
+pub fn strain(length: f32, rest_length: f32) -> f32 {
+    (length - rest_length) / rest_length.max(1e-6)
+}
+
+/// The strain a spring takes before it yields. `permanence_threshold` runs
+/// from 0 to -1 like the testbed slider: 0 yields to any strain, -0.1 past
+/// 10%, and -1, the default, never yields.
+pub fn yield_strain(permanence_threshold: f32) -> Option<f32> {
+    (permanence_threshold > -1.0).then(|| -permanence_threshold.min(0.0))
+}
+
+/// The rest length a spring yields to when its strain is past
+/// [`yield_strain`], None while it is within it. The spring is left
+/// strained by exactly that much, so it still pulls back but no longer all
+/// the way.
+pub fn yielded(length: f32, rest_length: f32, permanence_threshold: f32) -> Option<f32> {
+    let limit = yield_strain(permanence_threshold)?;
+    let strain = strain(length, rest_length);
+    if strain > limit {
+        Some(length / (1.0 + limit))
+    } else if strain < -limit {
+        Some(length / (1.0 - limit).max(1e-6))
+    } else {
+        None
+    }
+}
+
+fn length(spring: &Spring, points: &[Vector2<f32>]) -> Option<f32> {
+    Some((points.get(spring.particle_b)? - points.get(spring.particle_a)?).magnitude())
+}
+
+/// Whether a spring is stretched past `break_strain`.
+fn breaks(length: f32, rest_length: f32, break_strain: Option<f32>) -> bool {
+    break_strain.map_or(false, |limit| strain(length, rest_length) > limit)
+}
+
+/// Yields and breaks `springs` between `points` in place, pushing the
+/// removed ones onto `broken`.
+fn deform(
+    springs: &mut Vec<Spring>,
+    points: &[Vector2<f32>],
+    polygon: Option<usize>,
+    break_strain: Option<f32>,
+    broken: &mut Vec<Broken>,
+) {
+    springs.retain_mut(|spring| {
+        let Some(length) = length(spring, points) else {
+            return true;
+        };
+        if breaks(length, spring.rest_length, break_strain) {
+            broken.push(Broken {
+                polygon,
+                particle_a: spring.particle_a,
+                particle_b: spring.particle_b,
+                rest_length: spring.rest_length,
+                length,
+            });
+            return false;
+        }
+        if let Some(rest_length) = yielded(length, spring.rest_length, spring.permanence_threshold)
+        {
+            spring.rest_length = rest_length;
+        }
+        true
+    });
+}
+
+pub trait SolverPlasticity {
+    /// Lets every spring inside a polygon stretched or compressed past its
+    /// `permanence_threshold` take a new rest length, see [`yielded`], and
+    /// removes the ones stretched past `break_strain`. Springs are changed
+    /// in place, so this is cheap enough to run every update. Returns the
+    /// removed springs.
+    fn deform_polygon_springs(&mut self, break_strain: Option<f32>) -> Vec<Broken>;
+
+    /// The same for particle springs.
+    fn deform_particle_springs(&mut self, break_strain: Option<f32>) -> Vec<Broken>;
+}
+
+impl SolverPlasticity for Solver {
+    fn deform_polygon_springs(&mut self, break_strain: Option<f32>) -> Vec<Broken> {
+        let mut broken = Vec::new();
+        for p in 0..self.get_polygons_len() {
+            let Some(polygon) = self.get_polygon_mut(p) else {
+                continue;
+            };
+            let points: Vec<Vector2<f32>> = polygon.particles.iter().map(|p| p.pos).collect();
+            deform(
+                &mut polygon.particle_springs,
+                &points,
+                Some(p),
+                break_strain,
+                &mut broken,
+            );
+        }
+        broken
+    }
+
+    fn deform_particle_springs(&mut self, break_strain: Option<f32>) -> Vec<Broken> {
+        let points: Vec<Vector2<f32>> = self.get_particles().iter().map(|p| p.pos).collect();
+        let mut broken = Vec::new();
+        deform(
+            self.get_particle_springs_mut(),
+            &points,
+            None,
+            break_strain,
+            &mut broken,
+        );
+        broken
+    }
+}
@@ -0,0 +1,101 @@
+use bendy2d::polygon::Polygon;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// How the gas inside a polygon pushes on its outline.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PressureModel {
+    /// The same pressure whatever the area, so the body keeps growing
+    /// until its springs hold it.
+    Constant { pressure: f32 },
+    /// An ideal gas, `p = nRT / A`, with `nRT` chosen so the pressure is
+    /// `pressure` at the rest area. Squeezing the body raises the pressure
+    /// and stretching it lowers it.
+    IdealGas { pressure: f32 },
+    /// No gas, the outline is moved straight back towards the rest area by
+    /// a `stiffness` fraction of the difference every update.
+    TargetArea { stiffness: f32 },
+}
+
+/// Pressure inside a polygon, applied by `World` after every update in place
+/// of the pressure `bendy2d` applies itself.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pressure {
+    pub model: PressureModel,
+    /// Area the model is measured against. Raising it inflates the body
+    /// and lowering it deflates it. Unused by `Constant`.
+    pub rest_area: f32,
+}
+
+/// Area enclosed by a polygon's vertices, in either winding.
+pub fn area(polygon: &Polygon) -> f32 {
+    signed_area(polygon).abs()
+}
+
+fn signed_area(polygon: &Polygon) -> f32 {
+    let points = &polygon.particles;
+    (0..points.len())
+        .map(|i| points[i].pos.perp(&points[(i + 1) % points.len()].pos))
+        .sum::<f32>()
+        / 2.0
+}
+
+/// How the area grows as each vertex moves, pointing out of the polygon.
+/// Each is half the outward normals of the vertex's two edges, scaled by
+/// their lengths.
+fn area_gradient(polygon: &Polygon) -> Vec<Vector2<f32>> {
+    let points = &polygon.particles;
+    let n = points.len();
+    let winding = signed_area(polygon).signum();
+    (0..n)
+        .map(|i| {
+            let across = points[(i + 1) % n].pos - points[(i + n - 1) % n].pos;
+            Vector2::new(across.y, -across.x) * (winding / 2.0)
+        })
+        .collect()
+}
+
+impl Pressure {
+    /// Starts a pressure model with the polygon's current area at rest.
+    pub fn new(polygon: &Polygon, model: PressureModel) -> Self {
+        Self {
+            model,
+            rest_area: area(polygon),
+        }
+    }
+
+    /// The pressure of the gas at `area`, zero for `TargetArea`.
+    pub fn pressure(&self, area: f32) -> f32 {
+        match self.model {
+            PressureModel::Constant { pressure } => pressure,
+            PressureModel::IdealGas { pressure } => pressure * self.rest_area / area.max(1e-6),
+            PressureModel::TargetArea { .. } => 0.0,
+        }
+    }
+
+    /// Pushes the vertices out by the pressure over one update of `dt`,
+    /// each vertex taken as a unit mass, or straight towards the rest area
+    /// for `TargetArea`. Only positions change, so the push shows up as
+    /// velocity on the next update.
+    pub fn apply(&self, polygon: &mut Polygon, dt: f32) {
+        if polygon.particles.len() < 3 {
+            return;
+        }
+        let area = area(polygon);
+        let gradient = area_gradient(polygon);
+        let scale = match self.model {
+            PressureModel::TargetArea { stiffness } => {
+                let length: f32 = gradient.iter().map(|g| g.norm_squared()).sum();
+                if length < 1e-12 {
+                    return;
+                }
+                stiffness.clamp(0.0, 1.0) * (self.rest_area - area) / length
+            }
+            _ => self.pressure(area) * dt * dt,
+        };
+        for (particle, gradient) in polygon.particles.iter_mut().zip(gradient.iter()) {
+            particle.pos += gradient * scale;
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::broad_phase::Aabb;
+use crate::pick::{distance_to_segment, BodyRef};
+use bendy2d::common::is_point_in_polygon;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+
+/// Where a ray first meets a body.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// A polygon, circle or static line. Free particles have no size and
+    /// are never hit.
+    pub body: BodyRef,
+    /// For polygons, the edge from vertex `edge` to the next one.
+    pub edge: Option<usize>,
+    pub point: Vector2<f32>,
+    /// Unit normal of the surface at `point`, facing back along the ray.
+    pub normal: Vector2<f32>,
+    /// Distance from the start of the ray to `point`.
+    pub distance: f32,
+}
+
+pub trait SolverQuery {
+    /// The nearest body along the ray from `origin` in `direction`, within
+    /// `max_distance`.
+    fn raycast(
+        &self,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+    ) -> Option<RayHit>;
+
+    /// Every body along the ray, nearest first. A polygon can be hit once
+    /// per edge it crosses.
+    fn raycast_all(
+        &self,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+    ) -> Vec<RayHit>;
+
+    /// Polygons and circles containing `pos`.
+    fn bodies_at(&self, pos: Vector2<f32>) -> Vec<BodyRef>;
+
+    /// Particles, circles, polygons and static lines whose boxes overlap
+    /// `aabb`.
+    fn query_aabb(&self, aabb: &Aabb) -> Vec<BodyRef>;
+
+    /// Particles, circles, polygons and static lines overlapping the
+    /// polygon `shape`, given as its vertices in order.
+    fn query_shape(&self, shape: &[Vector2<f32>]) -> Vec<BodyRef>;
+}
+
+/// Distance along the ray to where it crosses the segment `a` → `b`.
+fn ray_segment(
+    origin: Vector2<f32>,
+    direction: Vector2<f32>,
+    a: Vector2<f32>,
+    b: Vector2<f32>,
+) -> Option<f32> {
+    let along = b - a;
+    let denominator = direction.perp(&along);
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+    let offset = a - origin;
+    let t = offset.perp(&along) / denominator;
+    let u = offset.perp(&direction) / denominator;
+    (t >= 0.0 && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+/// Distance along the ray to where it enters the circle, or 0 if it starts
+/// inside.
+fn ray_circle(
+    origin: Vector2<f32>,
+    direction: Vector2<f32>,
+    center: Vector2<f32>,
+    radius: f32,
+) -> Option<f32> {
+    let offset = origin - center;
+    let c = offset.magnitude_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let b = offset.dot(&direction);
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    Some(-b - discriminant.sqrt())
+}
+
+fn facing(normal: Vector2<f32>, direction: Vector2<f32>) -> Vector2<f32> {
+    match normal.dot(&direction) > 0.0 {
+        true => -normal,
+        false => normal,
+    }
+}
+
+fn segment_normal(a: Vector2<f32>, b: Vector2<f32>) -> Vector2<f32> {
+    let along = b - a;
+    Vector2::new(-along.y, along.x)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vector2::y())
+}
+
+fn segments_cross(a: Vector2<f32>, b: Vector2<f32>, c: Vector2<f32>, d: Vector2<f32>) -> bool {
+    ray_segment(a, b - a, c, d).map_or(false, |t| t <= 1.0)
+}
+
+/// Whether the segment `a` → `b` touches the polygon `shape`.
+fn segment_in_shape(a: Vector2<f32>, b: Vector2<f32>, shape: &[Vector2<f32>]) -> bool {
+    is_point_in_polygon(&a, &shape.to_vec()).is_some()
+        || (0..shape.len()).any(|i| segments_cross(a, b, shape[i], shape[(i + 1) % shape.len()]))
+}
+
+impl SolverQuery for Solver {
+    fn raycast(
+        &self,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        self.raycast_all(origin, direction, max_distance)
+            .into_iter()
+            .next()
+    }
+
+    fn raycast_all(
+        &self,
+        origin: Vector2<f32>,
+        direction: Vector2<f32>,
+        max_distance: f32,
+    ) -> Vec<RayHit> {
+        let Some(direction) = direction.try_normalize(f32::EPSILON) else {
+            return Vec::new();
+        };
+        let mut hits = Vec::new();
+        let mut hit = |body: BodyRef, edge: Option<usize>, distance: f32, normal: Vector2<f32>| {
+            if distance <= max_distance {
+                hits.push(RayHit {
+                    body,
+                    edge,
+                    point: origin + direction * distance,
+                    normal: facing(normal, direction),
+                    distance,
+                });
+            }
+        };
+
+        for (index, polygon) in self.get_polygons().iter().enumerate() {
+            let particles = &polygon.particles;
+            for i in 0..particles.len() {
+                let (a, b) = (particles[i].pos, particles[(i + 1) % particles.len()].pos);
+                if let Some(distance) = ray_segment(origin, direction, a, b) {
+                    hit(
+                        BodyRef::Polygon { index },
+                        Some(i),
+                        distance,
+                        segment_normal(a, b),
+                    );
+                }
+            }
+        }
+        for (index, circle) in self.get_circles().iter().enumerate() {
+            let center = circle.point.pos;
+            if let Some(distance) = ray_circle(origin, direction, center, circle.radius) {
+                let point = origin + direction * distance;
+                let normal = (point - center)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(-direction);
+                hit(BodyRef::Circle { index }, None, distance, normal);
+            }
+        }
+        for (index, line) in self.get_static_lines().iter().enumerate() {
+            if let Some(distance) = ray_segment(origin, direction, line.0, line.1) {
+                hit(
+                    BodyRef::StaticLine { index },
+                    None,
+                    distance,
+                    segment_normal(line.0, line.1),
+                );
+            }
+        }
+
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    fn bodies_at(&self, pos: Vector2<f32>) -> Vec<BodyRef> {
+        let mut bodies = Vec::new();
+        for (index, polygon) in self.get_polygons().iter().enumerate() {
+            let points: Vec<Vector2<f32>> = polygon.particles.iter().map(|p| p.pos).collect();
+            if is_point_in_polygon(&pos, &points).is_some() {
+                bodies.push(BodyRef::Polygon { index });
+            }
+        }
+        for (index, circle) in self.get_circles().iter().enumerate() {
+            if (circle.point.pos - pos).magnitude() <= circle.radius {
+                bodies.push(BodyRef::Circle { index });
+            }
+        }
+        bodies
+    }
+
+    fn query_aabb(&self, aabb: &Aabb) -> Vec<BodyRef> {
+        let mut bodies = Vec::new();
+        for (index, particle) in self.get_particles().iter().enumerate() {
+            if aabb.overlaps(&Aabb::from_points([&particle.pos])) {
+                bodies.push(BodyRef::Particle { index });
+            }
+        }
+        for (index, circle) in self.get_circles().iter().enumerate() {
+            let radius = Vector2::repeat(circle.radius);
+            let bounds = Aabb {
+                min: circle.point.pos - radius,
+                max: circle.point.pos + radius,
+            };
+            if aabb.overlaps(&bounds) {
+                bodies.push(BodyRef::Circle { index });
+            }
+        }
+        for (index, polygon) in self.get_polygons().iter().enumerate() {
+            let bounds = Aabb::from_points(polygon.particles.iter().map(|p| &p.pos));
+            if aabb.overlaps(&bounds) {
+                bodies.push(BodyRef::Polygon { index });
+            }
+        }
+        for (index, line) in self.get_static_lines().iter().enumerate() {
+            if aabb.overlaps(&Aabb::from_points([&line.0, &line.1])) {
+                bodies.push(BodyRef::StaticLine { index });
+            }
+        }
+        bodies
+    }
+
+    fn query_shape(&self, shape: &[Vector2<f32>]) -> Vec<BodyRef> {
+        if shape.len() < 3 {
+            return Vec::new();
+        }
+        let outline = shape.to_vec();
+        let inside = |pos: &Vector2<f32>| is_point_in_polygon(pos, &outline).is_some();
+        let edges = || (0..shape.len()).map(|i| (shape[i], shape[(i + 1) % shape.len()]));
+
+        let mut bodies = Vec::new();
+        for (index, particle) in self.get_particles().iter().enumerate() {
+            if inside(&particle.pos) {
+                bodies.push(BodyRef::Particle { index });
+            }
+        }
+        for (index, circle) in self.get_circles().iter().enumerate() {
+            let center = circle.point.pos;
+            if inside(&center)
+                || edges().any(|(a, b)| distance_to_segment(center, a, b) <= circle.radius)
+            {
+                bodies.push(BodyRef::Circle { index });
+            }
+        }
+        for (index, polygon) in self.get_polygons().iter().enumerate() {
+            let points: Vec<Vector2<f32>> = polygon.particles.iter().map(|p| p.pos).collect();
+            let overlaps = shape
+                .first()
+                .map_or(false, |pos| is_point_in_polygon(pos, &points).is_some())
+                || (0..points.len())
+                    .any(|i| segment_in_shape(points[i], points[(i + 1) % points.len()], shape));
+            if overlaps {
+                bodies.push(BodyRef::Polygon { index });
+            }
+        }
+        for (index, line) in self.get_static_lines().iter().enumerate() {
+            if segment_in_shape(line.0, line.1, shape) {
+                bodies.push(BodyRef::StaticLine { index });
+            }
+        }
+        bodies
+    }
+}
@@ -0,0 +1,155 @@
+use crate::pick::BodyRef;
+use crate::scene::{LinkDesc, SpringDesc};
+use crate::snapshot::Snapshot;
+use bendy2d::link::{CircleLink, ParticleLink};
+use bendy2d::solver::Solver;
+use bendy2d::spring::Spring;
+
+/// Where an index ends up once the item at `removed` is swap-removed from a
+/// list whose last index is `last`, `None` for the removed item itself.
+pub fn moved(index: usize, removed: usize, last: usize) -> Option<usize> {
+    if index == removed {
+        None
+    } else if index == last {
+        Some(removed)
+    } else {
+        Some(index)
+    }
+}
+
+/// A spring or link, joining two items of another list by index.
+trait Ends {
+    fn ends(&mut self) -> (&mut usize, &mut usize);
+}
+
+impl Ends for SpringDesc {
+    fn ends(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.particle_a, &mut self.particle_b)
+    }
+}
+
+impl Ends for LinkDesc {
+    fn ends(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.particle_a, &mut self.particle_b)
+    }
+}
+
+impl Ends for Spring {
+    fn ends(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.particle_a, &mut self.particle_b)
+    }
+}
+
+impl Ends for ParticleLink {
+    fn ends(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.link.particle_a, &mut self.link.particle_b)
+    }
+}
+
+impl Ends for CircleLink {
+    fn ends(&mut self) -> (&mut usize, &mut usize) {
+        (&mut self.link.particle_a, &mut self.link.particle_b)
+    }
+}
+
+/// Swap-removes the item at `index`, returning the index the last item had
+/// before it moved into its place, or None if there was nothing there.
+fn swap_remove<T>(items: &mut Vec<T>, index: usize) -> Option<usize> {
+    let last = items.len().checked_sub(1)?;
+    if index > last {
+        return None;
+    }
+    items.swap_remove(index);
+    Some(last)
+}
+
+/// Drops the springs or links joined to the item swap-removed at `removed`
+/// and points the ones joined to the last item at its new index.
+fn remap<T: Ends>(joins: &mut Vec<T>, removed: usize, last: usize) {
+    joins.retain_mut(|join| {
+        let (a, b) = join.ends();
+        match (moved(*a, removed, last), moved(*b, removed, last)) {
+            (Some(new_a), Some(new_b)) => {
+                *a = new_a;
+                *b = new_b;
+                true
+            }
+            _ => false,
+        }
+    });
+}
+
+impl Snapshot {
+    /// Takes a body out of the snapshot the same way
+    /// [`SolverRemove::remove`] takes it out of a solver.
+    pub fn remove(&mut self, body: BodyRef) -> bool {
+        match body {
+            BodyRef::Particle { index } => match swap_remove(&mut self.particles, index) {
+                Some(last) => {
+                    remap(&mut self.springs, index, last);
+                    remap(&mut self.particle_links, index, last);
+                    true
+                }
+                None => false,
+            },
+            BodyRef::Circle { index } => match swap_remove(&mut self.circles, index) {
+                Some(last) => {
+                    remap(&mut self.circle_links, index, last);
+                    true
+                }
+                None => false,
+            },
+            BodyRef::Polygon { index } => swap_remove(&mut self.polygons, index).is_some(),
+            BodyRef::Spring { index } => swap_remove(&mut self.springs, index).is_some(),
+            BodyRef::ParticleLink { index } => {
+                swap_remove(&mut self.particle_links, index).is_some()
+            }
+            BodyRef::CircleLink { index } => swap_remove(&mut self.circle_links, index).is_some(),
+            BodyRef::StaticLine { index } => swap_remove(&mut self.static_lines, index).is_some(),
+        }
+    }
+}
+
+pub trait SolverRemove {
+    /// Takes a body out of the solver's lists in place. The last item of
+    /// its list moves into its place, so every other index stays the same.
+    /// Springs and links attached to a removed particle or circle go with
+    /// it, and the ones attached to the moved one follow it. Returns false
+    /// if there was nothing at that index.
+    fn remove(&mut self, body: BodyRef) -> bool;
+}
+
+impl SolverRemove for Solver {
+    fn remove(&mut self, body: BodyRef) -> bool {
+        match body {
+            BodyRef::Particle { index } => match swap_remove(self.get_particles_mut(), index) {
+                Some(last) => {
+                    remap(self.get_particle_springs_mut(), index, last);
+                    remap(self.get_particle_links_mut(), index, last);
+                    true
+                }
+                None => false,
+            },
+            BodyRef::Circle { index } => match swap_remove(self.get_circles_mut(), index) {
+                Some(last) => {
+                    remap(self.get_circle_links_mut(), index, last);
+                    true
+                }
+                None => false,
+            },
+            BodyRef::Polygon { index } => swap_remove(self.get_polygons_mut(), index).is_some(),
+            BodyRef::Spring { index } => {
+                swap_remove(self.get_particle_springs_mut(), index).is_some()
+            }
+            BodyRef::ParticleLink { index } => {
+                swap_remove(self.get_particle_links_mut(), index).is_some()
+            }
+            BodyRef::CircleLink { index } => {
+                swap_remove(self.get_circle_links_mut(), index).is_some()
+            }
+            BodyRef::StaticLine { index } => {
+                swap_remove(self.get_static_lines_mut(), index).is_some()
+            }
+        }
+    }
+}
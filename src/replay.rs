@@ -0,0 +1,397 @@
+use crate::broad_phase::BroadPhase;
+use crate::connector::{Connector, Endpoint};
+use crate::filter::CollisionFilter;
+use crate::joint::JointDesc;
+use crate::kinematic::Motion;
+use crate::material::{CombineRules, Material};
+use crate::pick::{self, BodyRef, PointRef};
+use crate::pressure::Pressure;
+use crate::scene::{CircleDesc, PolygonShape, Scene, SceneBounds, SpringDesc};
+use crate::world::{
+    CircleLink, ConnectorId, HandleError, JointId, ParticleLink, PolygonId, Spring, Timestep, World,
+};
+use bendy2d::solver::{Bounds, Solver};
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+pub const FORMAT_VERSION: u32 = 4;
+
+/// One change made to the world by the user.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    AddParticle {
+        pos: Vector2<f32>,
+    },
+    AddSpring(Spring),
+    AddParticleLink(ParticleLink),
+    AddCircle(CircleDesc),
+    AddCircleLink(CircleLink),
+    AddPolygon(PolygonShape),
+    AddStaticLine {
+        line: [Vector2<f32>; 2],
+    },
+    Reset {
+        gravity: Vector2<f32>,
+        bounds: SceneBounds,
+    },
+    LoadScene(Scene),
+    /// Pulls a point a fraction `stiffness` of the way towards `target`, one
+    /// step of a mouse drag. The grab tool now pulls with a connector, see
+    /// [`Action::MoveConnectorEnd`]; this is kept for older recordings.
+    Drag {
+        point: PointRef,
+        target: Vector2<f32>,
+        stiffness: f32,
+    },
+    /// Lets go of a dragged point, leaving it moving by `velocity` per
+    /// `Solver::update`, see [`Timestep::per_update`].
+    Throw {
+        point: PointRef,
+        velocity: Vector2<f32>,
+    },
+    /// Moves a point without changing its velocity.
+    MovePoint {
+        point: PointRef,
+        pos: Vector2<f32>,
+    },
+    /// Moves every particle of a polygon without changing their velocity.
+    MovePolygon {
+        index: usize,
+        offset: Vector2<f32>,
+    },
+    SetSpring {
+        index: usize,
+        spring: SpringDesc,
+    },
+    SetCircleRadius {
+        index: usize,
+        radius: f32,
+    },
+    Remove {
+        body: BodyRef,
+    },
+    SetSweptCollisions {
+        enabled: bool,
+    },
+    SetBroadPhase {
+        broad_phase: BroadPhase,
+    },
+    SetSweptThreads {
+        count: usize,
+    },
+    SetTimestep(Timestep),
+    /// Sets the material of a body, see [`World::set_material`].
+    SetMaterial {
+        body: BodyRef,
+        material: Material,
+    },
+    SetCombineRules(CombineRules),
+    SetBreakStrain {
+        break_strain: Option<f32>,
+    },
+    SetCollisionFilter {
+        body: BodyRef,
+        filter: CollisionFilter,
+    },
+    AddJoint(JointDesc),
+    RemoveJoint {
+        joint: JointId,
+    },
+    AddConnector(Connector),
+    RemoveConnector {
+        connector: ConnectorId,
+    },
+    /// Moves the `b` end of a connector to a fixed point in the world, as
+    /// the grab tool does with the mouse every step.
+    MoveConnectorEnd {
+        connector: ConnectorId,
+        pos: Vector2<f32>,
+    },
+    /// Makes a polygon kinematic with `motion`, or simulated again for None.
+    SetMotion {
+        polygon: PolygonId,
+        motion: Option<Motion>,
+    },
+    /// Replaces the pressure of a polygon, or takes it away for None.
+    SetPressure {
+        polygon: PolygonId,
+        pressure: Option<Pressure>,
+    },
+    /// Turns shape matching on with `stiffness`, or off for None.
+    SetShapeMatch {
+        polygon: PolygonId,
+        stiffness: Option<f32>,
+    },
+    /// Spawn parameters chosen with the sliders. They only affect what is
+    /// spawned next, but are kept so a replay shows the same values.
+    Settings(Settings),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    pub radius: f32,
+    pub point_count: usize,
+    pub stiffness: f32,
+    pub pressure: f32,
+    pub permanence_threshold: f32,
+}
+
+impl Action {
+    /// Applies the action, failing if it refers to a body that no longer
+    /// exists.
+    pub fn apply(&self, world: &mut World) -> Result<(), HandleError> {
+        match self {
+            Action::AddParticle { pos } => {
+                world.add_particle(*pos);
+            }
+            Action::AddSpring(spring) => world.add_particle_spring(spring)?,
+            Action::AddParticleLink(link) => world.add_particle_link(link)?,
+            Action::AddCircle(circle) => {
+                world.add_circle(circle.circle());
+            }
+            Action::AddCircleLink(link) => world.add_circle_link(link)?,
+            Action::AddPolygon(shape) => {
+                world.add_polygon(shape.build());
+            }
+            Action::AddStaticLine { line } => world.add_static_line((line[0], line[1])),
+            Action::Reset { gravity, bounds } => {
+                let mut solver = Solver::new();
+                solver.gravity = *gravity;
+                solver.bounds = Bounds {
+                    pos: bounds.pos,
+                    size: bounds.size,
+                };
+                world.reset(solver);
+            }
+            Action::LoadScene(scene) => world.load_scene(scene),
+            Action::Drag {
+                point,
+                target,
+                stiffness,
+            } => {
+                if let Some(particle) = pick::point_particle_mut(world.solver_mut(), *point) {
+                    particle.pos += (target - particle.pos) * *stiffness;
+                }
+            }
+            Action::Throw { point, velocity } => {
+                if let Some(particle) = pick::point_particle_mut(world.solver_mut(), *point) {
+                    particle.prev_pos = particle.pos - velocity;
+                }
+            }
+            Action::MovePoint { point, pos } => {
+                if let Some(particle) = pick::point_particle_mut(world.solver_mut(), *point) {
+                    particle.prev_pos += pos - particle.pos;
+                    particle.pos = *pos;
+                }
+            }
+            Action::MovePolygon { index, offset } => {
+                if let Some(polygon) = world.solver_mut().get_polygon_mut(*index) {
+                    for particle in polygon.particles.iter_mut() {
+                        particle.pos += offset;
+                        particle.prev_pos += offset;
+                    }
+                }
+            }
+            Action::SetSpring { index, spring } => {
+                let springs = world.solver_mut().get_particle_springs_mut();
+                if let Some(edited) = springs.get_mut(*index) {
+                    *edited = spring.spring();
+                }
+            }
+            Action::SetCircleRadius { index, radius } => {
+                if let Some(circle) = world.solver_mut().get_circle_mut(*index) {
+                    circle.radius = *radius;
+                }
+            }
+            Action::Remove { body } => {
+                world.remove(*body);
+            }
+            Action::SetSweptCollisions { enabled } => world.set_swept_collisions(*enabled),
+            Action::SetBroadPhase { broad_phase } => world.set_broad_phase(*broad_phase),
+            Action::SetSweptThreads { count } => world.set_swept_threads(*count),
+            Action::SetTimestep(timestep) => world.set_timestep(*timestep),
+            Action::SetMaterial { body, material } => {
+                world.set_material(*body, *material);
+            }
+            Action::SetCombineRules(combine) => world.set_combine_rules(*combine),
+            Action::SetBreakStrain { break_strain } => world.set_break_strain(*break_strain),
+            Action::SetCollisionFilter { body, filter } => {
+                world.set_collision_filter(*body, *filter);
+            }
+            Action::AddJoint(joint) => {
+                world.add_joint(joint)?;
+            }
+            Action::RemoveJoint { joint } => world.remove_joint(*joint)?,
+            Action::AddConnector(connector) => {
+                world.add_connector(connector)?;
+            }
+            Action::RemoveConnector { connector } => world.remove_connector(*connector)?,
+            Action::MoveConnectorEnd { connector, pos } => {
+                world.connector_mut(*connector)?.b = Endpoint::World { pos: *pos };
+            }
+            Action::SetMotion { polygon, motion } => match motion {
+                Some(motion) => world.set_kinematic(*polygon, *motion)?,
+                None => world.set_dynamic(*polygon)?,
+            },
+            Action::SetPressure { polygon, pressure } => world.set_pressure(*polygon, *pressure)?,
+            Action::SetShapeMatch { polygon, stiffness } => match stiffness {
+                Some(stiffness) => world.set_shape_match(*polygon, *stiffness)?,
+                None => world.clear_shape_match(*polygon)?,
+            },
+            Action::Settings(_) => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// Number of fixed steps taken before the action happened.
+    pub frame: usize,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// Everything needed to rebuild a session step by step. The first entries
+/// are always the world as it was when recording started, world section
+/// included, followed by the timestep and options it was stepped with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    pub version: u32,
+    pub entries: Vec<Entry>,
+}
+
+impl Recording {
+    /// Starts a recording of `world`, which `initial` was captured from with
+    /// [`Scene::from_world`].
+    pub fn new(world: &World, initial: Scene) -> Self {
+        let actions = [
+            Action::LoadScene(initial),
+            Action::SetTimestep(world.timestep()),
+            Action::SetSweptCollisions {
+                enabled: world.swept_collisions(),
+            },
+            Action::SetBroadPhase {
+                broad_phase: world.broad_phase(),
+            },
+            Action::SetSweptThreads {
+                count: world.swept_threads(),
+            },
+            Action::SetBreakStrain {
+                break_strain: world.break_strain(),
+            },
+        ];
+        Self {
+            version: FORMAT_VERSION,
+            entries: actions
+                .into_iter()
+                .map(|action| Entry { frame: 0, action })
+                .collect(),
+        }
+    }
+
+    pub fn record(&mut self, frame: usize, action: Action) {
+        self.entries.push(Entry { frame, action });
+    }
+
+    /// Drops everything recorded at or after `frame`, used when the world is
+    /// rewound to the state right before that frame.
+    pub fn truncate(&mut self, frame: usize) {
+        self.entries.retain(|entry| entry.frame < frame);
+    }
+
+    pub fn last_frame(&self) -> usize {
+        self.entries.last().map(|entry| entry.frame).unwrap_or(0)
+    }
+
+    pub fn load(path: &str) -> std::io::Result<Recording> {
+        let mut data = String::new();
+        File::open(path)?.read_to_string(&mut data)?;
+        let recording: Recording = serde_json::from_str(&data)?;
+        if recording.version != FORMAT_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "replay format version {} is not supported, expected {}",
+                    recording.version, FORMAT_VERSION
+                ),
+            ));
+        }
+        for entry in recording.entries.iter() {
+            match &entry.action {
+                Action::AddPolygon(shape) => shape
+                    .check()
+                    .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?,
+                Action::LoadScene(scene) => scene.check()?,
+                Action::SetTimestep(timestep) if !timestep.is_valid() => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "timestep step must be positive and finite, got {}",
+                            timestep.step
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        File::create(path)?.write_all(data.as_bytes())
+    }
+}
+
+/// Hands out the recorded actions frame by frame.
+pub struct Replayer {
+    recording: Recording,
+    next: usize,
+}
+
+impl Replayer {
+    pub fn new(recording: Recording) -> Self {
+        Self { recording, next: 0 }
+    }
+
+    /// Actions to apply before stepping `frame`, in the order they happened.
+    pub fn actions_for(&mut self, frame: usize) -> Vec<Action> {
+        let start = self.next;
+        while self.next < self.recording.entries.len()
+            && self.recording.entries[self.next].frame <= frame
+        {
+            self.next += 1;
+        }
+        self.recording.entries[start..self.next]
+            .iter()
+            .map(|entry| entry.action.clone())
+            .collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.entries.len()
+    }
+}
+
+/// Rebuilds the world of a recording without a window, stepping until
+/// every action has been applied. Fails if an action refers to a body that
+/// was already removed, meaning the recording doesn't match this solver.
+pub fn replay(recording: Recording) -> Result<World, HandleError> {
+    let mut world = World::new();
+    let mut replayer = Replayer::new(recording);
+    let mut frame = 0;
+    loop {
+        for action in replayer.actions_for(frame) {
+            action.apply(&mut world)?;
+        }
+        if replayer.is_finished() {
+            return Ok(world);
+        }
+        world.step();
+        frame += 1;
+    }
+}
@@ -0,0 +1,61 @@
+use crate::scenarios::Scenario;
+use bendy2d::solver::Solver;
+use nalgebra::Vector2;
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+/// Every polygon particle position in the solver, polygon by polygon.
+pub fn polygon_positions(solver: &Solver) -> Vec<Vector2<f32>> {
+    solver
+        .get_polygons()
+        .iter()
+        .flat_map(|polygon| polygon.particles.iter().map(|particle| particle.pos))
+        .collect()
+}
+
+/// Steps the scenario to the end without a window and records the polygon
+/// particle positions after every step.
+pub fn run_positions(scenario: &mut Scenario) -> Vec<Vec<Vector2<f32>>> {
+    let mut positions = Vec::with_capacity(scenario.steps);
+    for _ in 0..scenario.steps {
+        scenario.solver.update(scenario.dt);
+        positions.push(polygon_positions(&scenario.solver));
+    }
+    positions
+}
+
+/// Steps the scenario to the end without a window and records how long each
+/// `Solver::update` call took, in seconds.
+pub fn run_latencies(scenario: &mut Scenario) -> Vec<f32> {
+    let mut latencies = Vec::with_capacity(scenario.steps);
+    let clock = Instant::now();
+    for _ in 0..scenario.steps {
+        let last_frame_time = clock.elapsed().as_secs_f32();
+        scenario.solver.update(scenario.dt);
+        latencies.push(clock.elapsed().as_secs_f32() - last_frame_time);
+    }
+    latencies
+}
+
+pub fn write_positions_csv(path: &str, positions: &[Vec<Vector2<f32>>]) -> std::io::Result<()> {
+    let mut data = String::new();
+    for frame in positions.iter() {
+        for (i, point) in frame.iter().enumerate() {
+            data += &format!("{},{}", point.x, point.y);
+            if i != frame.len() - 1 {
+                data += ",";
+            }
+        }
+        data += "\n";
+    }
+    File::create(path)?.write_all(data.as_bytes())
+}
+
+pub fn write_latencies_csv(path: &str, latencies: &[f32]) -> std::io::Result<()> {
+    let mut data = String::new();
+    for latency in latencies.iter() {
+        data += &format!("{}\n", latency);
+    }
+    File::create(path)?.write_all(data.as_bytes())
+}
@@ -0,0 +1,235 @@
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::{Bounds, Solver};
+use nalgebra::Vector2;
+
+/// Names accepted by [`by_name`], in the order the binaries were written.
+pub const NAMES: [&str; 6] = [
+    "deterministic",
+    "performance1",
+    "performance2",
+    "performance3",
+    "high_speeds",
+    "pressure",
+];
+
+/// A prepared world together with how it is meant to be stepped.
+pub struct Scenario {
+    pub name: &'static str,
+    pub solver: Solver,
+    pub dt: f32,
+    pub steps: usize,
+    /// World units to pixels, only used when drawing.
+    pub scale: f32,
+}
+
+pub fn screen_size() -> Vector2<f32> {
+    Vector2::new(1920.0, 1080.0)
+}
+
+pub fn by_name(name: &str) -> Option<Scenario> {
+    match name {
+        "deterministic" => Some(deterministic()),
+        "performance1" => Some(performance1()),
+        "performance2" => Some(performance2()),
+        "performance3" => Some(performance3()),
+        "high_speeds" => Some(high_speeds()),
+        "pressure" => Some(pressure()),
+        _ => None,
+    }
+}
+
+/// 3x3 triangles falling under strong gravity.
+pub fn deterministic() -> Scenario {
+    let screen_size = screen_size();
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::new(0.0, 980.0);
+    solver.bounds = Bounds {
+        pos: Vector2::new(0.0, 0.0),
+        size: screen_size,
+    };
+
+    let size = 15.0;
+    let pos = Vector2::new(100.0, 100.0);
+    for x in 0..3 {
+        for y in 0..3 {
+            let index = Vector2::new(x as f32, y as f32);
+            solver.add_polygon(Polygon::circle(
+                size,
+                pos + Vector2::new(index.x * size * 2.0, index.y * size * 2.0),
+                3,
+                false,
+                500.0,
+                -1.0,
+            ));
+        }
+    }
+
+    Scenario {
+        name: "deterministic",
+        solver,
+        dt: 0.005,
+        steps: 3000,
+        scale: 1.0,
+    }
+}
+
+/// 15x15 grid of boxes.
+pub fn performance1() -> Scenario {
+    box_grid("performance1", 15)
+}
+
+/// 20x20 grid of pressure circles.
+pub fn performance2() -> Scenario {
+    let scale = 30.0;
+    let mut solver = scaled_solver(scale);
+
+    let size = 16.0;
+    let pos = Vector2::new(100.0, 100.0);
+    for x in 0..20 {
+        for y in 0..20 {
+            let index = Vector2::new(x as f32, y as f32);
+            solver.add_polygon(Polygon::pressure_circle(
+                size / scale,
+                (pos + Vector2::new(
+                    index.x * 8.0 + index.x * size * 2.0,
+                    index.y * 8.0 + index.y * size * 2.0,
+                )) / scale,
+                20,
+                false,
+                500.0,
+                30.0,
+            ));
+        }
+    }
+
+    Scenario {
+        name: "performance2",
+        solver,
+        dt: 1.0 / 60.0,
+        steps: 2000,
+        scale,
+    }
+}
+
+/// 50x15 grid of boxes.
+pub fn performance3() -> Scenario {
+    box_grid("performance3", 50)
+}
+
+/// Two boxes launched at each other fast enough to tunnel.
+pub fn high_speeds() -> Scenario {
+    let scale = 30.0;
+    let screen_size = screen_size();
+    let mut solver = Solver::new();
+    solver.bounds = Bounds {
+        pos: Vector2::new(0.0, 0.0),
+        size: screen_size / scale,
+    };
+
+    let size = Vector2::new(100.0, 100.0);
+    let pos1 = Vector2::new(200.0, 200.0);
+    let pos2 = Vector2::new(1600.0, 200.0);
+    solver.add_polygon(Polygon::new_box(
+        pos1 / scale,
+        0.0,
+        size / scale,
+        4.0,
+        50.0,
+        false,
+        -1.0,
+    ));
+
+    solver.add_polygon(Polygon::new_box(
+        pos2 / scale,
+        0.0,
+        size / scale,
+        4.0,
+        50.0,
+        false,
+        -1.0,
+    ));
+
+    let polygon1 = solver.get_polygon_mut(0).unwrap();
+    for particle in polygon1.particles.iter_mut() {
+        particle.prev_pos = particle.pos - Vector2::new(10.0, 0.0);
+    }
+    let polygon2 = solver.get_polygon_mut(1).unwrap();
+    for particle in polygon2.particles.iter_mut() {
+        particle.prev_pos = particle.pos + Vector2::new(10.0, 0.0);
+    }
+
+    Scenario {
+        name: "high_speeds",
+        solver,
+        dt: 1.0 / 60.0,
+        steps: 600,
+        scale,
+    }
+}
+
+/// A short column of pressure circles settling on the floor.
+pub fn pressure() -> Scenario {
+    let scale = 30.0;
+    let mut solver = scaled_solver(scale);
+
+    let size = 40.0;
+    for i in 0..3 {
+        solver.add_polygon(Polygon::pressure_circle(
+            size / scale,
+            Vector2::new(300.0 + i as f32 * 10.0, 700.0 - i as f32 * size * 2.5) / scale,
+            12,
+            false,
+            500.0,
+            30.0,
+        ));
+    }
+
+    Scenario {
+        name: "pressure",
+        solver,
+        dt: 1.0 / 60.0,
+        steps: 500,
+        scale,
+    }
+}
+
+fn scaled_solver(scale: f32) -> Solver {
+    let mut solver = Solver::new();
+    solver.bounds = Bounds {
+        pos: Vector2::new(0.0, 0.0),
+        size: screen_size() / scale,
+    };
+    solver.gravity = Vector2::new(0.0, 0.98);
+    solver
+}
+
+fn box_grid(name: &'static str, columns: usize) -> Scenario {
+    let scale = 30.0;
+    let mut solver = scaled_solver(scale);
+
+    let size = Vector2::new(32.0, 32.0);
+    let pos = Vector2::new(100.0, 100.0);
+    for x in 0..columns {
+        for y in 0..15 {
+            let index = Vector2::new(x as f32, y as f32);
+            solver.add_polygon(Polygon::new_box(
+                (pos + Vector2::new(index.x + index.x * size.x, index.y + index.y * size.y))
+                    / scale,
+                0.0,
+                size / scale,
+                4.0,
+                200.0,
+                false,
+                -1.0,
+            ));
+        }
+    }
+
+    Scenario {
+        name,
+        solver,
+        dt: 1.0 / 60.0,
+        steps: 2000,
+        scale,
+    }
+}
@@ -0,0 +1,519 @@
+use crate::outline::{self, OutlineError};
+use crate::world::{World, WorldState};
+use bendy2d::circle::Circle;
+use bendy2d::link::{CircleLink, Link, ParticleLink};
+use bendy2d::particle::Particle;
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d::spring::Spring;
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Write};
+
+/// A whole `Solver` world as stored in a `.json` scene file.
+///
+/// Indices in springs and links refer to the `particles` and `circles`
+/// lists of the same scene, in file order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scene {
+    /// Leaves the `Solver::new` default in place when missing.
+    #[serde(default)]
+    pub gravity: Option<Vector2<f32>>,
+    pub bounds: SceneBounds,
+    #[serde(default)]
+    pub polygons: Vec<PolygonDesc>,
+    #[serde(default)]
+    pub particles: Vec<ParticleDesc>,
+    #[serde(default)]
+    pub springs: Vec<SpringDesc>,
+    #[serde(default)]
+    pub particle_links: Vec<LinkDesc>,
+    #[serde(default)]
+    pub circles: Vec<CircleDesc>,
+    #[serde(default)]
+    pub circle_links: Vec<LinkDesc>,
+    #[serde(default)]
+    pub static_lines: Vec<[Vector2<f32>; 2]>,
+    /// Ids of the bodies and what `World` attaches to them, such as
+    /// materials, collision filters, joints, connectors, kinematic motions,
+    /// shape matching and pressure. Missing from scenes written by hand, whose bodies
+    /// get ids in list order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world: Option<WorldState>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneBounds {
+    pub pos: Vector2<f32>,
+    pub size: Vector2<f32>,
+}
+
+/// The constructor a polygon was made with and its arguments.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum PolygonShape {
+    Circle {
+        radius: f32,
+        pos: Vector2<f32>,
+        point_count: usize,
+        fixed: bool,
+        stiffness: f32,
+        #[serde(default = "default_permanence_threshold")]
+        permanence_threshold: f32,
+    },
+    PressureCircle {
+        radius: f32,
+        pos: Vector2<f32>,
+        point_count: usize,
+        fixed: bool,
+        stiffness: f32,
+        pressure: f32,
+    },
+    Box {
+        pos: Vector2<f32>,
+        #[serde(default)]
+        rotation: f32,
+        size: Vector2<f32>,
+        mass: f32,
+        stiffness: f32,
+        fixed: bool,
+        #[serde(default = "default_permanence_threshold")]
+        permanence_threshold: f32,
+    },
+    /// Any simple outline, concave or not, braced by springs along its
+    /// triangulation. See [`outline::polygon`].
+    Outline {
+        points: Vec<Vector2<f32>>,
+        fixed: bool,
+        stiffness: f32,
+        #[serde(default = "default_permanence_threshold")]
+        permanence_threshold: f32,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolygonDesc {
+    #[serde(flatten)]
+    pub shape: PolygonShape,
+    /// Initial displacement per step, applied as `prev_pos = pos - velocity`.
+    #[serde(default)]
+    pub velocity: Vector2<f32>,
+    /// Particle state saved from a running solver. When present it replaces
+    /// the positions the constructor produced.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub particles: Vec<ParticleDesc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParticleDesc {
+    pub pos: Vector2<f32>,
+    /// Defaults to `pos`, a particle at rest.
+    #[serde(default)]
+    pub prev_pos: Option<Vector2<f32>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpringDesc {
+    pub particle_a: usize,
+    pub particle_b: usize,
+    pub rest_length: f32,
+    pub stiffness: f32,
+    #[serde(default = "default_permanence_threshold")]
+    pub permanence_threshold: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkDesc {
+    pub particle_a: usize,
+    pub particle_b: usize,
+    pub target_distance: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircleDesc {
+    pub pos: Vector2<f32>,
+    #[serde(default)]
+    pub prev_pos: Option<Vector2<f32>>,
+    pub radius: f32,
+}
+
+fn default_permanence_threshold() -> f32 {
+    -1.0
+}
+
+/// Fails if an end of one of the springs or links, given as the indices
+/// they join, is past the end of the list it indexes into.
+fn check_ends(
+    kind: &str,
+    ends: impl Iterator<Item = (usize, usize)>,
+    list: &str,
+    len: usize,
+) -> std::io::Result<()> {
+    for (i, (a, b)) in ends.enumerate() {
+        if let Some(index) = [a, b].into_iter().find(|index| *index >= len) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} {} refers to {} {} but there are {}",
+                    kind, i, list, index, len
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl SceneBounds {
+    pub fn from_bounds(bounds: &Bounds) -> Self {
+        Self {
+            pos: bounds.pos,
+            size: bounds.size,
+        }
+    }
+}
+
+impl PolygonShape {
+    /// Fails for an outline that can't be triangulated, which `build` would
+    /// panic on.
+    pub fn check(&self) -> Result<(), OutlineError> {
+        match self {
+            PolygonShape::Outline { points, .. } => outline::triangulate(points).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Panics for an outline that `check` rejects.
+    pub fn build(&self) -> Polygon {
+        match *self {
+            PolygonShape::Circle {
+                radius,
+                pos,
+                point_count,
+                fixed,
+                stiffness,
+                permanence_threshold,
+            } => Polygon::circle(
+                radius,
+                pos,
+                point_count,
+                fixed,
+                stiffness,
+                permanence_threshold,
+            ),
+            PolygonShape::PressureCircle {
+                radius,
+                pos,
+                point_count,
+                fixed,
+                stiffness,
+                pressure,
+            } => Polygon::pressure_circle(radius, pos, point_count, fixed, stiffness, pressure),
+            PolygonShape::Box {
+                pos,
+                rotation,
+                size,
+                mass,
+                stiffness,
+                fixed,
+                permanence_threshold,
+            } => Polygon::new_box(
+                pos,
+                rotation,
+                size,
+                mass,
+                stiffness,
+                fixed,
+                permanence_threshold,
+            ),
+            PolygonShape::Outline {
+                ref points,
+                fixed,
+                stiffness,
+                permanence_threshold,
+            } => outline::polygon(points, fixed, stiffness, permanence_threshold)
+                .unwrap_or_else(|error| panic!("invalid polygon outline: {}", error)),
+        }
+    }
+}
+
+impl ParticleDesc {
+    fn particle(&self) -> Particle {
+        let mut particle = Particle::new(self.pos);
+        particle.prev_pos = self.prev_pos.unwrap_or(self.pos);
+        particle
+    }
+
+    fn from_particle(particle: &Particle) -> Self {
+        Self {
+            pos: particle.pos,
+            prev_pos: Some(particle.prev_pos),
+        }
+    }
+}
+
+impl Scene {
+    pub fn load(path: &str) -> std::io::Result<Scene> {
+        let mut data = String::new();
+        File::open(path)?.read_to_string(&mut data)?;
+        let scene: Scene = serde_json::from_str(&data)?;
+        scene.check()?;
+        Ok(scene)
+    }
+
+    /// Fails if a polygon can't be built, see [`PolygonShape::check`], has
+    /// saved particles for another number of vertices than its shape makes,
+    /// a spring or link refers to a particle or circle that isn't in the
+    /// scene, or the world section was saved with other bodies.
+    pub fn check(&self) -> std::io::Result<()> {
+        let invalid = |message: String| Error::new(ErrorKind::InvalidData, message);
+        for (i, polygon) in self.polygons.iter().enumerate() {
+            polygon
+                .shape
+                .check()
+                .map_err(|error| invalid(format!("polygon {}: {}", i, error)))?;
+            let vertices = polygon.shape.build().particles.len();
+            if !polygon.particles.is_empty() && polygon.particles.len() != vertices {
+                return Err(invalid(format!(
+                    "polygon {} has {} saved particles but its shape has {} vertices",
+                    i,
+                    polygon.particles.len(),
+                    vertices
+                )));
+            }
+        }
+
+        let particles = self.particles.len();
+        let circles = self.circles.len();
+        let ends = |desc: &SpringDesc| (desc.particle_a, desc.particle_b);
+        check_ends(
+            "spring",
+            self.springs.iter().map(ends),
+            "particle",
+            particles,
+        )?;
+        let ends = |desc: &LinkDesc| (desc.particle_a, desc.particle_b);
+        check_ends(
+            "particle link",
+            self.particle_links.iter().map(ends),
+            "particle",
+            particles,
+        )?;
+        check_ends(
+            "circle link",
+            self.circle_links.iter().map(ends),
+            "circle",
+            circles,
+        )?;
+
+        if let Some(world) = &self.world {
+            if !world.fits(
+                self.particles.len(),
+                self.circles.len(),
+                self.polygons.len(),
+                self.static_lines.len(),
+            ) {
+                return Err(invalid(
+                    "the world section doesn't match the bodies of the scene".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        File::create(path)?.write_all(data.as_bytes())
+    }
+
+    /// Builds a fresh solver containing everything in the scene. Expects a
+    /// scene that passes [`Scene::check`].
+    pub fn build(&self) -> Solver {
+        let mut solver = Solver::new();
+        if let Some(gravity) = self.gravity {
+            solver.gravity = gravity;
+        }
+        solver.bounds = Bounds {
+            pos: self.bounds.pos,
+            size: self.bounds.size,
+        };
+
+        for (i, desc) in self.polygons.iter().enumerate() {
+            solver.add_polygon(desc.shape.build());
+            let polygon = solver.get_polygon_mut(i).unwrap();
+            if desc.particles.len() == polygon.particles.len() {
+                for (particle, saved) in polygon.particles.iter_mut().zip(desc.particles.iter()) {
+                    *particle = saved.particle();
+                }
+            }
+            for particle in polygon.particles.iter_mut() {
+                particle.prev_pos -= desc.velocity;
+            }
+        }
+
+        for (i, desc) in self.particles.iter().enumerate() {
+            solver.add_particle(desc.pos);
+            if let Some(particle) = solver.get_particle_mut(i) {
+                *particle = desc.particle();
+            }
+        }
+        for spring in self.springs.iter() {
+            solver.add_particle_spring(spring.spring());
+        }
+        for link in self.particle_links.iter() {
+            solver.add_particle_link(ParticleLink {
+                link: link.link(),
+            });
+        }
+
+        for circle in self.circles.iter() {
+            solver.add_circle(circle.circle());
+        }
+        for link in self.circle_links.iter() {
+            solver.add_circle_link(CircleLink {
+                link: link.link(),
+            });
+        }
+
+        for line in self.static_lines.iter() {
+            solver.add_static_line((line[0], line[1]));
+        }
+
+        solver
+    }
+
+    /// Captures the current state of a running solver.
+    ///
+    /// The solver does not remember which constructor made each polygon, so
+    /// `shapes` must list them in the order the polygons were added.
+    pub fn from_solver(solver: &Solver, shapes: &[PolygonShape]) -> std::io::Result<Scene> {
+        let polygons = solver.get_polygons();
+        if polygons.len() != shapes.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} polygons in the solver but {} shapes were given",
+                    polygons.len(),
+                    shapes.len()
+                ),
+            ));
+        }
+
+        Ok(Scene {
+            gravity: Some(solver.gravity),
+            bounds: SceneBounds::from_bounds(&solver.bounds),
+            polygons: polygons
+                .iter()
+                .zip(shapes.iter())
+                .map(|(polygon, shape)| PolygonDesc {
+                    shape: shape.clone(),
+                    velocity: Vector2::zeros(),
+                    particles: polygon
+                        .particles
+                        .iter()
+                        .map(ParticleDesc::from_particle)
+                        .collect(),
+                })
+                .collect(),
+            particles: solver
+                .get_particles()
+                .iter()
+                .map(ParticleDesc::from_particle)
+                .collect(),
+            springs: solver
+                .get_particle_springs()
+                .iter()
+                .map(SpringDesc::from_spring)
+                .collect(),
+            particle_links: solver
+                .get_particle_links()
+                .iter()
+                .map(|link| LinkDesc::from_link(&link.link))
+                .collect(),
+            circles: solver
+                .get_circles()
+                .iter()
+                .map(CircleDesc::from_circle)
+                .collect(),
+            circle_links: solver
+                .get_circle_links()
+                .iter()
+                .map(|link| LinkDesc::from_link(&link.link))
+                .collect(),
+            static_lines: solver
+                .get_static_lines()
+                .iter()
+                .map(|line| [line.0, line.1])
+                .collect(),
+            world: None,
+        })
+    }
+
+    /// Captures a running world, its solver as [`Scene::from_solver`] does
+    /// and the rest as its world section.
+    pub fn from_world(world: &World, shapes: &[PolygonShape]) -> std::io::Result<Scene> {
+        Ok(Scene {
+            world: Some(world.state()),
+            ..Scene::from_solver(world, shapes)?
+        })
+    }
+}
+
+impl SpringDesc {
+    pub fn spring(&self) -> Spring {
+        Spring {
+            particle_a: self.particle_a,
+            particle_b: self.particle_b,
+            rest_length: self.rest_length,
+            stiffness: self.stiffness,
+            permanence_threshold: self.permanence_threshold,
+        }
+    }
+
+    pub fn from_spring(spring: &Spring) -> Self {
+        Self {
+            particle_a: spring.particle_a,
+            particle_b: spring.particle_b,
+            rest_length: spring.rest_length,
+            stiffness: spring.stiffness,
+            permanence_threshold: spring.permanence_threshold,
+        }
+    }
+}
+
+impl CircleDesc {
+    pub fn circle(&self) -> Circle {
+        let mut point = Particle::new(self.pos);
+        point.prev_pos = self.prev_pos.unwrap_or(self.pos);
+        Circle {
+            point,
+            radius: self.radius,
+        }
+    }
+
+    pub fn from_circle(circle: &Circle) -> Self {
+        Self {
+            pos: circle.point.pos,
+            prev_pos: Some(circle.point.prev_pos),
+            radius: circle.radius,
+        }
+    }
+}
+
+impl LinkDesc {
+    pub fn link(&self) -> Link {
+        Link {
+            particle_a: self.particle_a,
+            particle_b: self.particle_b,
+            target_distance: self.target_distance,
+        }
+    }
+
+    pub fn from_link(link: &Link) -> Self {
+        Self {
+            particle_a: link.particle_a,
+            particle_b: link.particle_b,
+            target_distance: link.target_distance,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use bendy2d::polygon::Polygon;
+use nalgebra::{Rotation2, Vector2};
+use serde::{Deserialize, Serialize};
+
+/// Mean of a polygon's vertices.
+pub fn center(polygon: &Polygon) -> Vector2<f32> {
+    let sum: Vector2<f32> = polygon.particles.iter().map(|p| p.pos).sum();
+    sum / polygon.particles.len().max(1) as f32
+}
+
+/// The center of a polygon and the angle that best turns `rest`, vertex
+/// offsets from the center, onto its current vertices. Only a rotation is
+/// fitted, so the result is never mirrored even when the polygon is
+/// turned inside out.
+pub fn fit(polygon: &Polygon, rest: &[Vector2<f32>]) -> (Vector2<f32>, f32) {
+    let center = center(polygon);
+    let (mut cross, mut dot) = (0.0, 0.0);
+    for (particle, rest) in polygon.particles.iter().zip(rest.iter()) {
+        let offset = particle.pos - center;
+        cross += rest.perp(&offset);
+        dot += rest.dot(&offset);
+    }
+    (center, cross.atan2(dot))
+}
+
+/// Pulls a polygon's vertices towards its rest shape, moved and turned to
+/// fit where the vertices are now. Soft polygons then wobble back after an
+/// impact instead of staying crumpled or inverted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShapeMatch {
+    /// Fraction of the way to the fitted shape each vertex moves per
+    /// update, from 0 for no effect to 1 for a rigid polygon.
+    pub stiffness: f32,
+    // Vertex offsets from the center in the rest shape
+    rest: Vec<Vector2<f32>>,
+}
+
+impl ShapeMatch {
+    /// Takes the polygon's current shape as its rest shape.
+    pub fn new(polygon: &Polygon, stiffness: f32) -> Self {
+        let center = center(polygon);
+        Self {
+            stiffness,
+            rest: polygon.particles.iter().map(|p| p.pos - center).collect(),
+        }
+    }
+
+    /// Moves every vertex towards the fitted rest shape. Only positions
+    /// change, so the pull shows up as velocity on the next update.
+    pub fn solve(&self, polygon: &mut Polygon) {
+        if polygon.particles.len() != self.rest.len() {
+            return;
+        }
+        let (center, angle) = fit(polygon, &self.rest);
+        let rotation = Rotation2::new(angle);
+        let stiffness = self.stiffness.clamp(0.0, 1.0);
+        for (particle, rest) in polygon.particles.iter_mut().zip(self.rest.iter()) {
+            let goal = center + rotation * rest;
+            particle.pos += (goal - particle.pos) * stiffness;
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::scene::{CircleDesc, LinkDesc, SpringDesc};
+use bendy2d::link::{CircleLink, ParticleLink};
+use bendy2d::particle::Particle;
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::{Bounds, Solver};
+use nalgebra::Vector2;
+
+/// A copy of everything in a `Solver` at one point in time.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub gravity: Vector2<f32>,
+    pub bounds_pos: Vector2<f32>,
+    pub bounds_size: Vector2<f32>,
+    pub polygons: Vec<Polygon>,
+    pub particles: Vec<Particle>,
+    pub springs: Vec<SpringDesc>,
+    pub particle_links: Vec<LinkDesc>,
+    pub circles: Vec<CircleDesc>,
+    pub circle_links: Vec<LinkDesc>,
+    pub static_lines: Vec<(Vector2<f32>, Vector2<f32>)>,
+}
+
+pub trait SolverSnapshot {
+    fn snapshot(&self) -> Snapshot;
+    /// Puts every body, spring, link and line of the snapshot back into the
+    /// solver's own lists, along with gravity and bounds. Particle `pos` and
+    /// `prev_pos` come back exactly, so stepping afterwards matches stepping
+    /// from the moment the snapshot was taken. The solver itself is kept,
+    /// so any setting of it the snapshot doesn't hold stays as it is.
+    fn restore(&mut self, snapshot: &Snapshot);
+}
+
+impl SolverSnapshot for Solver {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            gravity: self.gravity,
+            bounds_pos: self.bounds.pos,
+            bounds_size: self.bounds.size,
+            polygons: self.get_polygons().to_vec(),
+            particles: self.get_particles().to_vec(),
+            springs: self
+                .get_particle_springs()
+                .iter()
+                .map(SpringDesc::from_spring)
+                .collect(),
+            particle_links: self
+                .get_particle_links()
+                .iter()
+                .map(|link| LinkDesc::from_link(&link.link))
+                .collect(),
+            circles: self
+                .get_circles()
+                .iter()
+                .map(CircleDesc::from_circle)
+                .collect(),
+            circle_links: self
+                .get_circle_links()
+                .iter()
+                .map(|link| LinkDesc::from_link(&link.link))
+                .collect(),
+            static_lines: self.get_static_lines().to_vec(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.gravity = snapshot.gravity;
+        self.bounds = Bounds {
+            pos: snapshot.bounds_pos,
+            size: snapshot.bounds_size,
+        };
+        *self.get_polygons_mut() = snapshot.polygons.clone();
+        *self.get_particles_mut() = snapshot.particles.clone();
+        *self.get_particle_springs_mut() =
+            snapshot.springs.iter().map(SpringDesc::spring).collect();
+        *self.get_particle_links_mut() = snapshot
+            .particle_links
+            .iter()
+            .map(|link| ParticleLink { link: link.link() })
+            .collect();
+        *self.get_circles_mut() = snapshot.circles.iter().map(CircleDesc::circle).collect();
+        *self.get_circle_links_mut() = snapshot
+            .circle_links
+            .iter()
+            .map(|link| CircleLink { link: link.link() })
+            .collect();
+        *self.get_static_lines_mut() = snapshot.static_lines.clone();
+    }
+}
@@ -0,0 +1,55 @@
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
+
+/// Runs independent per-item work on a fixed number of threads. Results come
+/// back in item order and are applied by the caller one by one, so the
+/// thread count never changes the outcome. Only the passes `World` runs
+/// itself use it; `Solver::update` inside `bendy2d` stays single-threaded.
+#[derive(Clone, Default)]
+pub struct Threads {
+    // None runs everything on the calling thread
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl Threads {
+    pub fn new(count: usize) -> Self {
+        if count <= 1 {
+            return Self::default();
+        }
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(count)
+            .build()
+            .map(Arc::new)
+            .ok();
+        Self { pool }
+    }
+
+    pub fn count(&self) -> usize {
+        self.pool
+            .as_ref()
+            .map_or(1, |pool| pool.current_num_threads())
+    }
+
+    pub fn map<T, R>(&self, items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(|| items.par_iter().map(f).collect()),
+            None => items.iter().map(f).collect(),
+        }
+    }
+}
+
+/// Number of cores on this machine.
+pub fn available() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Every thread count from 1 up to the number of cores, the range the
+/// testbed slider offers.
+pub fn counts() -> Vec<usize> {
+    (1..=available()).collect()
+}
@@ -0,0 +1,1539 @@
+use crate::broad_phase::BroadPhase;
+use crate::ccd::SolverCcd;
+use crate::connector::{self, Connector, Endpoint, Resolved};
+use crate::contact::find_contacts;
+use crate::events::{self, ContactEvent, ContactListener, ContactTracker};
+use crate::filter::{CollisionFilter, FilterTable, SolverFilter};
+use crate::joint::{self, Joint, JointDesc};
+use crate::kinematic::{self, Kinematic, Motion, Transform};
+use crate::material::{respond, CombineRules, Material, MaterialTable, Velocities};
+use crate::pick::{self, BodyRef, PointRef};
+use crate::plasticity::{Broken, SolverPlasticity, SpringBreak, SpringBreakListener};
+use crate::pressure::{self, Pressure, PressureModel};
+use crate::remove::SolverRemove;
+use crate::scene::Scene;
+use crate::shape_match::ShapeMatch;
+use crate::snapshot::{Snapshot, SolverSnapshot};
+use crate::threads::Threads;
+use bendy2d::circle::Circle;
+use bendy2d::link::{self, Link};
+use bendy2d::particle::Particle;
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::Solver;
+use bendy2d::spring;
+use nalgebra::Vector2;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Deref;
+
+/// Handle to a free particle. Stays valid while other bodies are added and
+/// removed, and is never reused once its particle is gone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ParticleId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CircleId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PolygonId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct JointId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ConnectorId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandleError {
+    StaleParticle(ParticleId),
+    StaleCircle(CircleId),
+    StalePolygon(PolygonId),
+    StaleJoint(JointId),
+    StaleConnector(ConnectorId),
+    NoVertex(PolygonId, usize),
+}
+
+impl fmt::Display for HandleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandleError::StaleParticle(id) => write!(f, "particle {} no longer exists", id.0),
+            HandleError::StaleCircle(id) => write!(f, "circle {} no longer exists", id.0),
+            HandleError::StalePolygon(id) => write!(f, "polygon {} no longer exists", id.0),
+            HandleError::StaleJoint(id) => write!(f, "joint {} no longer exists", id.0),
+            HandleError::StaleConnector(id) => write!(f, "connector {} no longer exists", id.0),
+            HandleError::NoVertex(id, vertex) => {
+                write!(f, "polygon {} has no vertex {}", id.0, vertex)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HandleError {}
+
+/// `bendy2d::spring::Spring` between two particle handles.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Spring {
+    pub particle_a: ParticleId,
+    pub particle_b: ParticleId,
+    pub rest_length: f32,
+    pub stiffness: f32,
+    pub permanence_threshold: f32,
+}
+
+/// `bendy2d::link::ParticleLink` between two particle handles.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParticleLink {
+    pub particle_a: ParticleId,
+    pub particle_b: ParticleId,
+    pub target_distance: f32,
+}
+
+/// `bendy2d::link::CircleLink` between two circle handles.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircleLink {
+    pub circle_a: CircleId,
+    pub circle_b: CircleId,
+    pub target_distance: f32,
+}
+
+/// Id to index table for one of the solver's lists.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Slots {
+    // Current index of every id ever handed out, None once removed
+    indices: Vec<Option<usize>>,
+    // Id of the item at every index
+    ids: Vec<u32>,
+}
+
+impl Slots {
+    fn with_len(len: usize) -> Self {
+        Self {
+            indices: (0..len).map(Some).collect(),
+            ids: (0..len as u32).collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn insert(&mut self) -> u32 {
+        let id = self.indices.len() as u32;
+        self.indices.push(Some(self.ids.len()));
+        self.ids.push(id);
+        id
+    }
+
+    fn index(&self, id: u32) -> Option<usize> {
+        self.indices.get(id as usize).copied().flatten()
+    }
+
+    fn id(&self, index: usize) -> Option<u32> {
+        self.ids.get(index).copied()
+    }
+
+    // Swap-removes like the list the slots index into, so only the id of
+    // the last item moves
+    fn remove_index(&mut self, index: usize) {
+        let id = self.ids.swap_remove(index);
+        self.indices[id as usize] = None;
+        if let Some(last) = self.ids.get(index) {
+            self.indices[*last as usize] = Some(index);
+        }
+    }
+}
+
+/// How [`World::advance`] turns frame time into solver updates.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Timestep {
+    /// Length of one fixed step in seconds.
+    pub step: f32,
+    /// `Solver::update` calls per step, each advancing `step / substeps`.
+    pub substeps: u32,
+    /// Most steps one `advance` call will take. Time beyond that is dropped
+    /// so a slow frame doesn't make the next one slower still.
+    pub max_steps: u32,
+}
+
+impl Default for Timestep {
+    fn default() -> Self {
+        Self {
+            step: 0.01,
+            substeps: 1,
+            max_steps: 5,
+        }
+    }
+}
+
+impl Timestep {
+    /// Whether `World` can run this timestep. `step` has to be positive and
+    /// finite, otherwise the accumulator never runs down.
+    pub fn is_valid(&self) -> bool {
+        self.step.is_finite() && self.step > 0.0
+    }
+
+    /// Seconds advanced by one `Solver::update`.
+    pub fn update_dt(&self) -> f32 {
+        self.step / self.substeps.max(1) as f32
+    }
+
+    /// The distance per `Solver::update` of something that moved by
+    /// `distance` over `seconds`, the velocity a particle needs to keep
+    /// moving with it. Frames shorter than one update count as one.
+    pub fn per_update(&self, distance: Vector2<f32>, seconds: f32) -> Vector2<f32> {
+        let update_dt = self.update_dt();
+        distance * (update_dt / seconds.max(update_dt))
+    }
+}
+
+/// A `Solver` whose particles, circles and polygons are addressed by stable
+/// handles instead of their position in the solver's lists.
+///
+/// Reading goes straight through to the solver. Bodies must be added and
+/// removed through the world so the handles stay in sync.
+pub struct World {
+    solver: Solver,
+    particles: Slots,
+    circles: Slots,
+    polygons: Slots,
+    swept_collisions: bool,
+    broad_phase: BroadPhase,
+    threads: Threads,
+    timestep: Timestep,
+    // Frame time not yet taken as a step
+    accumulator: f32,
+    default_material: Material,
+    materials: PerBody<Material>,
+    combine: CombineRules,
+    filters: PerBody<CollisionFilter>,
+    contacts: ContactTracker,
+    joints: Vec<Joint>,
+    joint_slots: Slots,
+    connectors: Vec<Connector>,
+    connector_slots: Slots,
+    kinematic: HashMap<PolygonId, Kinematic>,
+    shape_matches: HashMap<PolygonId, ShapeMatch>,
+    pressures: HashMap<PolygonId, Pressure>,
+    break_strain: Option<f32>,
+    // Springs broken since the last fixed step started
+    spring_breaks: Vec<SpringBreak>,
+    spring_listeners: Vec<SpringBreakListener>,
+}
+
+/// Values given to single bodies. Bodies without an entry use a default.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize", deserialize = "T: Deserialize<'de>"))]
+struct PerBody<T> {
+    #[serde(with = "sorted_pairs")]
+    particles: HashMap<ParticleId, T>,
+    #[serde(with = "sorted_pairs")]
+    circles: HashMap<CircleId, T>,
+    #[serde(with = "sorted_pairs")]
+    polygons: HashMap<PolygonId, T>,
+    // One per static line, there are no handles for them
+    static_lines: Vec<Option<T>>,
+}
+
+/// Saves a map keyed by handles as a list of pairs in handle order, so
+/// files don't change from one save to the next and keys needn't be
+/// strings.
+mod sorted_pairs {
+    use super::*;
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Ord + Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        let mut pairs: Vec<(&K, &V)> = map.iter().collect();
+        pairs.sort_by(|a, b| a.0.cmp(b.0));
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Eq + Hash + Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// Handle to any body that can have a material or filter. Static lines
+/// have no handles of their own and are named by their index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum BodyId {
+    Particle(ParticleId),
+    Circle(CircleId),
+    Polygon(PolygonId),
+    StaticLine(usize),
+}
+
+impl<T: Copy> PerBody<T> {
+    fn with_lines(static_lines: usize) -> Self {
+        Self {
+            particles: HashMap::new(),
+            circles: HashMap::new(),
+            polygons: HashMap::new(),
+            static_lines: vec![None; static_lines],
+        }
+    }
+
+    fn get(&self, key: BodyId) -> Option<T> {
+        match key {
+            BodyId::Particle(id) => self.particles.get(&id).copied(),
+            BodyId::Circle(id) => self.circles.get(&id).copied(),
+            BodyId::Polygon(id) => self.polygons.get(&id).copied(),
+            BodyId::StaticLine(index) => self.static_lines.get(index).copied().flatten(),
+        }
+    }
+
+    fn insert(&mut self, key: BodyId, value: T) {
+        match key {
+            BodyId::Particle(id) => {
+                self.particles.insert(id, value);
+            }
+            BodyId::Circle(id) => {
+                self.circles.insert(id, value);
+            }
+            BodyId::Polygon(id) => {
+                self.polygons.insert(id, value);
+            }
+            BodyId::StaticLine(index) => self.static_lines[index] = Some(value),
+        }
+    }
+
+    fn remove(&mut self, key: BodyId) {
+        match key {
+            BodyId::Particle(id) => {
+                self.particles.remove(&id);
+            }
+            BodyId::Circle(id) => {
+                self.circles.remove(&id);
+            }
+            BodyId::Polygon(id) => {
+                self.polygons.remove(&id);
+            }
+            BodyId::StaticLine(index) => {
+                self.static_lines.swap_remove(index);
+            }
+        }
+    }
+
+    fn values(&self) -> impl Iterator<Item = &T> {
+        self.particles
+            .values()
+            .chain(self.circles.values())
+            .chain(self.polygons.values())
+            .chain(self.static_lines.iter().flatten())
+    }
+}
+
+/// Values by the current index of each body, see [`World::material_table`].
+#[derive(Clone, Debug, Default)]
+pub struct BodyTable<T> {
+    /// Used for anything past the end of the lists.
+    pub default: T,
+    pub particles: Vec<T>,
+    pub circles: Vec<T>,
+    pub polygons: Vec<T>,
+    pub static_lines: Vec<T>,
+}
+
+impl<T: Copy> BodyTable<T> {
+    pub fn body(&self, body: BodyRef) -> T {
+        let value = match body {
+            BodyRef::Particle { index } => self.particles.get(index),
+            BodyRef::Circle { index } => self.circles.get(index),
+            BodyRef::Polygon { index } => self.polygons.get(index),
+            BodyRef::StaticLine { index } => self.static_lines.get(index),
+            _ => None,
+        };
+        value.copied().unwrap_or(self.default)
+    }
+
+    /// The value of the body a point belongs to.
+    pub fn point(&self, point: PointRef) -> T {
+        self.body(point.body())
+    }
+}
+
+/// A [`Snapshot`] together with the rest of the world it came from.
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    pub snapshot: Snapshot,
+    state: WorldState,
+}
+
+/// What a `World` keeps beside its solver: the handle tables and what is
+/// attached to bodies through them. Saved with a scene, see
+/// [`Scene::world`], so its bodies keep their ids and what is attached to
+/// them when it is loaded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorldState {
+    particles: Slots,
+    circles: Slots,
+    polygons: Slots,
+    #[serde(default)]
+    default_material: Material,
+    materials: PerBody<Material>,
+    #[serde(default)]
+    combine: CombineRules,
+    filters: PerBody<CollisionFilter>,
+    #[serde(default)]
+    joints: Vec<Joint>,
+    #[serde(default)]
+    joint_slots: Slots,
+    #[serde(default)]
+    connectors: Vec<Connector>,
+    #[serde(default)]
+    connector_slots: Slots,
+    #[serde(default, with = "sorted_pairs")]
+    kinematic: HashMap<PolygonId, Kinematic>,
+    #[serde(default, with = "sorted_pairs")]
+    shape_matches: HashMap<PolygonId, ShapeMatch>,
+    #[serde(default, with = "sorted_pairs")]
+    pressures: HashMap<PolygonId, Pressure>,
+}
+
+impl WorldState {
+    /// Whether the state was taken from a world with as many of each body,
+    /// so it can be put back on one.
+    pub fn fits(
+        &self,
+        particles: usize,
+        circles: usize,
+        polygons: usize,
+        static_lines: usize,
+    ) -> bool {
+        self.particles.len() == particles
+            && self.circles.len() == circles
+            && self.polygons.len() == polygons
+            && self.materials.static_lines.len() == static_lines
+            && self.filters.static_lines.len() == static_lines
+            && self.joints.len() == self.joint_slots.len()
+            && self.connectors.len() == self.connector_slots.len()
+    }
+}
+
+impl Deref for World {
+    type Target = Solver;
+
+    fn deref(&self) -> &Solver {
+        &self.solver
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::from_solver(Solver::new())
+    }
+
+    /// Wraps a solver, handing out ids to what it already contains in list
+    /// order.
+    pub fn from_solver(solver: Solver) -> Self {
+        Self {
+            particles: Slots::with_len(solver.get_particle_len()),
+            circles: Slots::with_len(solver.get_circles_len()),
+            polygons: Slots::with_len(solver.get_polygons_len()),
+            materials: PerBody::with_lines(solver.get_static_lines().len()),
+            filters: PerBody::with_lines(solver.get_static_lines().len()),
+            solver,
+            swept_collisions: false,
+            broad_phase: BroadPhase::default(),
+            threads: Threads::default(),
+            timestep: Timestep::default(),
+            accumulator: 0.0,
+            default_material: Material::default(),
+            combine: CombineRules::default(),
+            contacts: ContactTracker::default(),
+            joints: Vec::new(),
+            joint_slots: Slots::default(),
+            connectors: Vec::new(),
+            connector_slots: Slots::default(),
+            kinematic: HashMap::new(),
+            shape_matches: HashMap::new(),
+            pressures: HashMap::new(),
+            break_strain: None,
+            spring_breaks: Vec::new(),
+            spring_listeners: Vec::new(),
+        }
+    }
+
+    /// Swaps in another solver with fresh ids, keeping the world's options,
+    /// default material and listeners.
+    pub fn reset(&mut self, solver: Solver) {
+        let mut contacts = std::mem::take(&mut self.contacts);
+        contacts.clear();
+        let spring_listeners = std::mem::take(&mut self.spring_listeners);
+        *self = Self {
+            swept_collisions: self.swept_collisions,
+            broad_phase: self.broad_phase,
+            threads: self.threads.clone(),
+            timestep: self.timestep,
+            default_material: self.default_material,
+            combine: self.combine,
+            contacts,
+            break_strain: self.break_strain,
+            spring_listeners,
+            ..Self::from_solver(solver)
+        };
+    }
+
+    /// Mutable access for changing bodies in place. Adding or removing
+    /// bodies through it leaves the handles out of sync.
+    pub fn solver_mut(&mut self) -> &mut Solver {
+        &mut self.solver
+    }
+
+    /// One `Solver::update` with the collision filters applied, followed by
+    /// pressure, shape matching, spring plasticity and breaking, joints,
+    /// connectors and kinematic polygons, friction and restitution
+    /// for every contact once any body has a material, and contact events
+    /// while they are enabled.
+    pub fn update(&mut self, dt: f32) {
+        let materials = self.uses_materials();
+        let before =
+            (materials || self.contacts.is_enabled()).then(|| Velocities::capture(&self.solver));
+        let filters = self.filter_table();
+        if self.swept_collisions {
+            self.solver
+                .update_swept(dt, self.broad_phase, &self.threads, &filters);
+        } else {
+            self.solver.update_filtered(dt, &filters);
+        }
+        if !self.pressures.is_empty() {
+            self.apply_pressure(dt);
+        }
+        if !self.shape_matches.is_empty() {
+            self.match_shapes();
+        }
+        let broken = self.solver.deform_polygon_springs(self.break_strain);
+        self.report_breaks(broken);
+        let broken = self.solver.deform_particle_springs(self.break_strain);
+        self.report_breaks(broken);
+        if !self.joints.is_empty() {
+            let polygons = &self.polygons;
+            joint::solve(&mut self.solver, &self.joints, |id| polygons.index(id.0));
+        }
+        if !self.connectors.is_empty() {
+            let ends: Vec<Option<(Resolved, Resolved)>> = self
+                .connectors
+                .iter()
+                .map(|c| Some((self.resolve(c.a)?, self.resolve(c.b)?)))
+                .collect();
+            connector::solve(&mut self.solver, &self.connectors, &ends, dt);
+        }
+        let kinematic = !self.kinematic.is_empty();
+        if kinematic {
+            self.place_kinematic(dt);
+        }
+        if before.is_some() || kinematic {
+            let contacts = find_contacts(&self.solver, self.broad_phase, &filters);
+            if kinematic {
+                let polygons = &self.polygons;
+                let bodies = &self.kinematic;
+                kinematic::push_out(&mut self.solver, &contacts, |index| {
+                    polygons
+                        .id(index)
+                        .map_or(false, |id| bodies.contains_key(&PolygonId(id)))
+                });
+            }
+            let Some(before) = before else {
+                return;
+            };
+            if materials {
+                let table = self.material_table();
+                respond(
+                    &mut self.solver,
+                    &contacts,
+                    &before,
+                    &table,
+                    &self.combine,
+                    dt,
+                );
+            }
+            if self.contacts.is_enabled() {
+                let touching = events::touching(&self.solver, &contacts, &before, dt, |body| {
+                    self.body_id(body)
+                });
+                self.contacts.update(touching);
+            }
+        }
+    }
+
+    /// One fixed step, split into the configured number of substeps. Contact
+    /// and spring break events from the step before are dropped first.
+    pub fn step(&mut self) {
+        self.contacts.clear_events();
+        self.spring_breaks.clear();
+        let dt = self.timestep.update_dt();
+        for _ in 0..self.timestep.substeps.max(1) {
+            self.update(dt);
+        }
+    }
+
+    fn report_breaks(&mut self, broken: Vec<Broken>) {
+        for broken in broken {
+            let ends = match broken.polygon {
+                Some(polygon) => self.polygon_id(polygon).map(|id| {
+                    let vertex = |vertex| Endpoint::PolygonVertex {
+                        polygon: id,
+                        vertex,
+                    };
+                    (vertex(broken.particle_a), vertex(broken.particle_b))
+                }),
+                None => self
+                    .particle_id(broken.particle_a)
+                    .zip(self.particle_id(broken.particle_b))
+                    .map(|(a, b)| {
+                        (
+                            Endpoint::Particle { particle: a },
+                            Endpoint::Particle { particle: b },
+                        )
+                    }),
+            };
+            let Some((a, b)) = ends else {
+                continue;
+            };
+            let pos = |endpoint: Endpoint| match self.resolve(endpoint)? {
+                Resolved::Point(point) => pick::point_particle(&self.solver, point).map(|p| p.pos),
+                Resolved::Fixed(pos) => Some(pos),
+            };
+            let (Some(pos_a), Some(pos_b)) = (pos(a), pos(b)) else {
+                continue;
+            };
+            let event = SpringBreak {
+                a,
+                b,
+                pos: (pos_a + pos_b) / 2.0,
+                rest_length: broken.rest_length,
+                length: broken.length,
+            };
+            for listener in self.spring_listeners.iter_mut() {
+                listener(&event);
+            }
+            self.spring_breaks.push(event);
+        }
+    }
+
+    /// Adds a frame's time to the accumulator and returns how many fixed
+    /// steps are now due, leaving the rest for the next frame. For callers
+    /// that need to act between steps, otherwise see [`World::advance`].
+    pub fn accumulate(&mut self, frame_dt: f32) -> usize {
+        self.accumulator += frame_dt;
+        let due = (self.accumulator / self.timestep.step).floor() as usize;
+        let steps = due.min(self.timestep.max_steps as usize);
+        self.accumulator -= due as f32 * self.timestep.step;
+        steps
+    }
+
+    /// Runs every fixed step due after `frame_dt` more seconds and returns
+    /// how many were taken.
+    pub fn advance(&mut self, frame_dt: f32) -> usize {
+        let steps = self.accumulate(frame_dt);
+        for _ in 0..steps {
+            self.step();
+        }
+        steps
+    }
+
+    /// How far the leftover frame time is into the next step, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.timestep.step
+    }
+
+    /// Where to draw a particle for the current `alpha`, between its
+    /// position one step ago and now. The position a step ago is estimated
+    /// from the last substep's velocity.
+    pub fn interpolated(&self, particle: &Particle) -> Vector2<f32> {
+        let step_motion = (particle.pos - particle.prev_pos) * self.timestep.substeps as f32;
+        particle.pos - step_motion * (1.0 - self.alpha())
+    }
+
+    pub fn timestep(&self) -> Timestep {
+        self.timestep
+    }
+
+    /// Panics if `timestep` isn't [`Timestep::is_valid`].
+    pub fn set_timestep(&mut self, timestep: Timestep) {
+        assert!(
+            timestep.is_valid(),
+            "timestep step must be positive and finite, got {}",
+            timestep.step
+        );
+        self.timestep = timestep;
+    }
+
+    /// Whether `update` also checks the paths points took during the step,
+    /// see [`SolverCcd::update_swept`].
+    pub fn swept_collisions(&self) -> bool {
+        self.swept_collisions
+    }
+
+    pub fn set_swept_collisions(&mut self, enabled: bool) {
+        self.swept_collisions = enabled;
+    }
+
+    /// How the swept pass and the contact search find the bodies worth
+    /// testing against each other. The solver's own collision step is not
+    /// affected, see [`BroadPhase`].
+    pub fn broad_phase(&self) -> BroadPhase {
+        self.broad_phase
+    }
+
+    pub fn set_broad_phase(&mut self, broad_phase: BroadPhase) {
+        self.broad_phase = broad_phase;
+    }
+
+    /// Threads the swept collision pass is spread over. The result is the
+    /// same for any count. `Solver::update` itself always runs on one
+    /// thread, so this only matters with swept collisions on.
+    pub fn swept_threads(&self) -> usize {
+        self.threads.count()
+    }
+
+    pub fn set_swept_threads(&mut self, count: usize) {
+        if count != self.threads.count() {
+            self.threads = Threads::new(count);
+        }
+    }
+
+    /// Whether any body has a material other than `Material::default()`.
+    /// Until one does, contacts are left to the solver alone.
+    pub fn uses_materials(&self) -> bool {
+        let none = Material::default();
+        self.default_material != none || self.materials.values().any(|m| *m != none)
+    }
+
+    /// The handle of a particle, circle, polygon or static line.
+    pub fn body_id(&self, body: BodyRef) -> Option<BodyId> {
+        match body {
+            BodyRef::Particle { index } => self.particle_id(index).map(BodyId::Particle),
+            BodyRef::Circle { index } => self.circle_id(index).map(BodyId::Circle),
+            BodyRef::Polygon { index } => self.polygon_id(index).map(BodyId::Polygon),
+            BodyRef::StaticLine { index } => {
+                (index < self.solver.get_static_lines().len()).then_some(BodyId::StaticLine(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Where a body currently is in the solver's lists.
+    pub fn body_ref(&self, id: BodyId) -> Option<BodyRef> {
+        match id {
+            BodyId::Particle(id) => self
+                .particle_index(id)
+                .ok()
+                .map(|index| BodyRef::Particle { index }),
+            BodyId::Circle(id) => self
+                .circle_index(id)
+                .ok()
+                .map(|index| BodyRef::Circle { index }),
+            BodyId::Polygon(id) => self
+                .polygon_index(id)
+                .ok()
+                .map(|index| BodyRef::Polygon { index }),
+            BodyId::StaticLine(index) => (index < self.solver.get_static_lines().len())
+                .then_some(BodyRef::StaticLine { index }),
+        }
+    }
+
+    fn table<T: Copy>(&self, values: &PerBody<T>, default: T) -> BodyTable<T> {
+        let value = |body| {
+            self.body_id(body)
+                .and_then(|key| values.get(key))
+                .unwrap_or(default)
+        };
+        BodyTable {
+            default,
+            particles: (0..self.solver.get_particle_len())
+                .map(|index| value(BodyRef::Particle { index }))
+                .collect(),
+            circles: (0..self.solver.get_circles_len())
+                .map(|index| value(BodyRef::Circle { index }))
+                .collect(),
+            polygons: (0..self.solver.get_polygons_len())
+                .map(|index| value(BodyRef::Polygon { index }))
+                .collect(),
+            static_lines: (0..self.solver.get_static_lines().len())
+                .map(|index| value(BodyRef::StaticLine { index }))
+                .collect(),
+        }
+    }
+
+    /// The material of a body. Free particles all share the default
+    /// material, and springs and links have none.
+    pub fn material(&self, body: BodyRef) -> Option<Material> {
+        let key = match body {
+            BodyRef::Particle { .. } => return Some(self.default_material),
+            _ => self.body_id(body)?,
+        };
+        Some(self.materials.get(key).unwrap_or(self.default_material))
+    }
+
+    /// Sets the material of a body, or the default material for a free
+    /// particle. Returns false if the body has no material.
+    pub fn set_material(&mut self, body: BodyRef, material: Material) -> bool {
+        match body {
+            BodyRef::Particle { .. } => self.default_material = material,
+            _ => match self.body_id(body) {
+                Some(key) => self.materials.insert(key, material),
+                None => return false,
+            },
+        }
+        true
+    }
+
+    pub fn set_circle_material(
+        &mut self,
+        id: CircleId,
+        material: Material,
+    ) -> Result<(), HandleError> {
+        self.circle_index(id)?;
+        self.materials.insert(BodyId::Circle(id), material);
+        Ok(())
+    }
+
+    pub fn set_polygon_material(
+        &mut self,
+        id: PolygonId,
+        material: Material,
+    ) -> Result<(), HandleError> {
+        self.polygon_index(id)?;
+        self.materials.insert(BodyId::Polygon(id), material);
+        Ok(())
+    }
+
+    /// Material of free particles and of every body that wasn't given one.
+    pub fn default_material(&self) -> Material {
+        self.default_material
+    }
+
+    pub fn set_default_material(&mut self, material: Material) {
+        self.default_material = material;
+    }
+
+    /// How the two materials of a contact are combined.
+    pub fn combine_rules(&self) -> CombineRules {
+        self.combine
+    }
+
+    pub fn set_combine_rules(&mut self, combine: CombineRules) {
+        self.combine = combine;
+    }
+
+    /// Every body's material by its current index.
+    pub fn material_table(&self) -> MaterialTable {
+        let mut table = self.table(&self.materials, self.default_material);
+        table.particles.clear();
+        table
+    }
+
+    /// The collision filter of a particle, circle, polygon or static line.
+    pub fn collision_filter(&self, body: BodyRef) -> Option<CollisionFilter> {
+        let key = self.body_id(body)?;
+        Some(self.filters.get(key).unwrap_or_default())
+    }
+
+    /// Returns false if the body can't have a filter.
+    pub fn set_collision_filter(&mut self, body: BodyRef, filter: CollisionFilter) -> bool {
+        match self.body_id(body) {
+            Some(key) => {
+                self.filters.insert(key, filter);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_particle_filter(
+        &mut self,
+        id: ParticleId,
+        filter: CollisionFilter,
+    ) -> Result<(), HandleError> {
+        self.particle_index(id)?;
+        self.filters.insert(BodyId::Particle(id), filter);
+        Ok(())
+    }
+
+    pub fn set_circle_filter(
+        &mut self,
+        id: CircleId,
+        filter: CollisionFilter,
+    ) -> Result<(), HandleError> {
+        self.circle_index(id)?;
+        self.filters.insert(BodyId::Circle(id), filter);
+        Ok(())
+    }
+
+    pub fn set_polygon_filter(
+        &mut self,
+        id: PolygonId,
+        filter: CollisionFilter,
+    ) -> Result<(), HandleError> {
+        self.polygon_index(id)?;
+        self.filters.insert(BodyId::Polygon(id), filter);
+        Ok(())
+    }
+
+    /// Every body's collision filter by its current index, empty while no
+    /// body has one.
+    pub fn filter_table(&self) -> FilterTable {
+        let none = CollisionFilter::default();
+        if self.filters.values().all(|filter| *filter == none) {
+            return FilterTable::default();
+        }
+        self.table(&self.filters, none)
+    }
+
+    /// Whether `update` keeps track of which bodies touch and sends contact
+    /// events. Off by default, as it costs a contact search every update.
+    pub fn contact_events_enabled(&self) -> bool {
+        self.contacts.is_enabled()
+    }
+
+    pub fn set_contact_events(&mut self, enabled: bool) {
+        self.contacts.set_enabled(enabled);
+    }
+
+    /// Calls `listener` with every contact event as it happens, and turns
+    /// contact events on.
+    pub fn on_contact(&mut self, listener: impl FnMut(&ContactEvent) + 'static) {
+        self.contacts
+            .add_listener(Box::new(listener) as ContactListener);
+        self.contacts.set_enabled(true);
+    }
+
+    /// Contact events of the current fixed step, or of every update since
+    /// the last `step` when calling `update` directly.
+    pub fn contact_events(&self) -> &[ContactEvent] {
+        self.contacts.events()
+    }
+
+    /// Strain past which springs break, None if they never do.
+    /// How far they stretch before their rest length gives is set per
+    /// spring by `permanence_threshold`, see [`plasticity::yielded`].
+    ///
+    /// [`plasticity::yielded`]: crate::plasticity::yielded
+    pub fn break_strain(&self) -> Option<f32> {
+        self.break_strain
+    }
+
+    pub fn set_break_strain(&mut self, break_strain: Option<f32>) {
+        self.break_strain = break_strain;
+    }
+
+    /// Calls `listener` with every spring as it breaks.
+    pub fn on_spring_break(&mut self, listener: impl FnMut(&SpringBreak) + 'static) {
+        self.spring_listeners
+            .push(Box::new(listener) as SpringBreakListener);
+    }
+
+    /// Springs broken during the current fixed step, or during every update
+    /// since the last `step` when calling `update` directly.
+    pub fn spring_breaks(&self) -> &[SpringBreak] {
+        &self.spring_breaks
+    }
+
+    /// Pairs of bodies touching after the last update.
+    pub fn contacts(&self) -> impl Iterator<Item = &ContactEvent> {
+        self.contacts.touching()
+    }
+
+    /// Bodies touching `body` after the last update.
+    pub fn contacts_of(&self, body: BodyId) -> impl Iterator<Item = &ContactEvent> {
+        self.contacts
+            .touching()
+            .filter(move |contact| contact.a == body || contact.b == body)
+    }
+
+    pub fn add_particle(&mut self, pos: Vector2<f32>) -> ParticleId {
+        self.solver.add_particle(pos);
+        ParticleId(self.particles.insert())
+    }
+
+    pub fn add_circle(&mut self, circle: Circle) -> CircleId {
+        self.solver.add_circle(circle);
+        CircleId(self.circles.insert())
+    }
+
+    pub fn add_polygon(&mut self, polygon: Polygon) -> PolygonId {
+        self.solver.add_polygon(polygon);
+        PolygonId(self.polygons.insert())
+    }
+
+    pub fn add_particle_spring(&mut self, spring: &Spring) -> Result<(), HandleError> {
+        let particle_a = self.particle_index(spring.particle_a)?;
+        let particle_b = self.particle_index(spring.particle_b)?;
+        self.solver.add_particle_spring(spring::Spring {
+            particle_a,
+            particle_b,
+            rest_length: spring.rest_length,
+            stiffness: spring.stiffness,
+            permanence_threshold: spring.permanence_threshold,
+        });
+        Ok(())
+    }
+
+    pub fn add_particle_link(&mut self, link: &ParticleLink) -> Result<(), HandleError> {
+        let particle_a = self.particle_index(link.particle_a)?;
+        let particle_b = self.particle_index(link.particle_b)?;
+        self.solver.add_particle_link(link::ParticleLink {
+            link: Link {
+                particle_a,
+                particle_b,
+                target_distance: link.target_distance,
+            },
+        });
+        Ok(())
+    }
+
+    pub fn add_circle_link(&mut self, link: &CircleLink) -> Result<(), HandleError> {
+        let particle_a = self.circle_index(link.circle_a)?;
+        let particle_b = self.circle_index(link.circle_b)?;
+        self.solver.add_circle_link(link::CircleLink {
+            link: Link {
+                particle_a,
+                particle_b,
+                target_distance: link.target_distance,
+            },
+        });
+        Ok(())
+    }
+
+    pub fn add_static_line(&mut self, line: (Vector2<f32>, Vector2<f32>)) {
+        self.solver.add_static_line(line);
+        self.materials.static_lines.push(None);
+        self.filters.static_lines.push(None);
+    }
+
+    pub fn particle_index(&self, id: ParticleId) -> Result<usize, HandleError> {
+        self.particles
+            .index(id.0)
+            .ok_or(HandleError::StaleParticle(id))
+    }
+
+    pub fn circle_index(&self, id: CircleId) -> Result<usize, HandleError> {
+        self.circles.index(id.0).ok_or(HandleError::StaleCircle(id))
+    }
+
+    pub fn polygon_index(&self, id: PolygonId) -> Result<usize, HandleError> {
+        self.polygons
+            .index(id.0)
+            .ok_or(HandleError::StalePolygon(id))
+    }
+
+    pub fn particle_id(&self, index: usize) -> Option<ParticleId> {
+        self.particles.id(index).map(ParticleId)
+    }
+
+    pub fn circle_id(&self, index: usize) -> Option<CircleId> {
+        self.circles.id(index).map(CircleId)
+    }
+
+    pub fn polygon_id(&self, index: usize) -> Option<PolygonId> {
+        self.polygons.id(index).map(PolygonId)
+    }
+
+    pub fn particle(&self, id: ParticleId) -> Result<&Particle, HandleError> {
+        let index = self.particle_index(id)?;
+        self.solver
+            .get_particle(index)
+            .ok_or(HandleError::StaleParticle(id))
+    }
+
+    pub fn particle_mut(&mut self, id: ParticleId) -> Result<&mut Particle, HandleError> {
+        let index = self.particle_index(id)?;
+        self.solver
+            .get_particle_mut(index)
+            .ok_or(HandleError::StaleParticle(id))
+    }
+
+    pub fn circle(&self, id: CircleId) -> Result<&Circle, HandleError> {
+        let index = self.circle_index(id)?;
+        self.solver
+            .get_circle(index)
+            .ok_or(HandleError::StaleCircle(id))
+    }
+
+    pub fn circle_mut(&mut self, id: CircleId) -> Result<&mut Circle, HandleError> {
+        let index = self.circle_index(id)?;
+        self.solver
+            .get_circle_mut(index)
+            .ok_or(HandleError::StaleCircle(id))
+    }
+
+    pub fn polygon(&self, id: PolygonId) -> Result<&Polygon, HandleError> {
+        let index = self.polygon_index(id)?;
+        self.solver
+            .get_polygons()
+            .get(index)
+            .ok_or(HandleError::StalePolygon(id))
+    }
+
+    pub fn polygon_mut(&mut self, id: PolygonId) -> Result<&mut Polygon, HandleError> {
+        let index = self.polygon_index(id)?;
+        self.solver
+            .get_polygon_mut(index)
+            .ok_or(HandleError::StalePolygon(id))
+    }
+
+    /// Removes a body by index, keeping the handles of everything else valid.
+    pub fn remove(&mut self, body: BodyRef) -> bool {
+        let key = self.body_id(body);
+        if !self.solver.remove(body) {
+            return false;
+        }
+        if let Some(key) = key {
+            self.materials.remove(key);
+            self.filters.remove(key);
+        }
+        match body {
+            BodyRef::Particle { index } => self.particles.remove_index(index),
+            BodyRef::Circle { index } => self.circles.remove_index(index),
+            BodyRef::Polygon { index } => {
+                self.polygons.remove_index(index);
+                if let Some(BodyId::Polygon(id)) = key {
+                    while let Some(joint) = self.joints.iter().position(|j| j.joins(id)) {
+                        self.joints.swap_remove(joint);
+                        self.joint_slots.remove_index(joint);
+                    }
+                }
+            }
+            _ => {}
+        }
+        if let Some(BodyId::Polygon(id)) = key {
+            self.kinematic.remove(&id);
+            self.shape_matches.remove(&id);
+            self.pressures.remove(&id);
+        }
+        if let Some(key) = key {
+            while let Some(connector) = self
+                .connectors
+                .iter()
+                .position(|c| c.a.body() == Some(key) || c.b.body() == Some(key))
+            {
+                self.connectors.swap_remove(connector);
+                self.connector_slots.remove_index(connector);
+            }
+        }
+        true
+    }
+
+    fn place_kinematic(&mut self, dt: f32) {
+        for (id, kinematic) in self.kinematic.iter_mut() {
+            kinematic.advance(dt);
+            if let Some(polygon) = self
+                .polygons
+                .index(id.0)
+                .and_then(|index| self.solver.get_polygon_mut(index))
+            {
+                kinematic.place(polygon, dt);
+            }
+        }
+    }
+
+    /// Makes a polygon follow `motion` from where it is now instead of
+    /// being simulated. It keeps its current shape and pushes other bodies
+    /// out of the way without being pushed back.
+    pub fn set_kinematic(&mut self, id: PolygonId, motion: Motion) -> Result<(), HandleError> {
+        let kinematic = Kinematic::new(self.polygon(id)?, motion);
+        self.kinematic.insert(id, kinematic);
+        Ok(())
+    }
+
+    /// Lets a kinematic polygon be simulated again, moving as it last did.
+    pub fn set_dynamic(&mut self, id: PolygonId) -> Result<(), HandleError> {
+        self.polygon_index(id)?;
+        self.kinematic.remove(&id);
+        Ok(())
+    }
+
+    /// The motion of a kinematic polygon, None if it is simulated.
+    pub fn motion(&self, id: PolygonId) -> Option<Motion> {
+        self.kinematic.get(&id).map(|kinematic| kinematic.motion)
+    }
+
+    /// Where a kinematic polygon should be by the next update, relative to
+    /// where it became kinematic. For motion scripted step by step, see
+    /// [`Motion::Manual`].
+    pub fn set_kinematic_transform(
+        &mut self,
+        id: PolygonId,
+        transform: Transform,
+    ) -> Result<(), HandleError> {
+        self.polygon_index(id)?;
+        match self.kinematic.get_mut(&id) {
+            Some(kinematic) => kinematic.motion = Motion::Manual(transform),
+            None => self.set_kinematic(id, Motion::Manual(transform))?,
+        }
+        Ok(())
+    }
+
+    fn apply_pressure(&mut self, dt: f32) {
+        for (id, pressure) in self.pressures.iter() {
+            if self.kinematic.contains_key(id) {
+                continue;
+            }
+            if let Some(polygon) = self
+                .polygons
+                .index(id.0)
+                .and_then(|index| self.solver.get_polygon_mut(index))
+            {
+                pressure.apply(polygon, dt);
+            }
+        }
+    }
+
+    /// Fills a polygon with gas following `model`, with the area it has now
+    /// at rest. A polygon that already has pressure keeps its rest area.
+    pub fn set_pressure_model(
+        &mut self,
+        id: PolygonId,
+        model: PressureModel,
+    ) -> Result<(), HandleError> {
+        let pressure = Pressure::new(self.polygon(id)?, model);
+        self.pressures
+            .entry(id)
+            .and_modify(|pressure| pressure.model = model)
+            .or_insert(pressure);
+        self.replace_builtin_pressure(id);
+        Ok(())
+    }
+
+    /// Replaces a polygon's pressure, or takes it away for None.
+    pub fn set_pressure(
+        &mut self,
+        id: PolygonId,
+        pressure: Option<Pressure>,
+    ) -> Result<(), HandleError> {
+        self.polygon_index(id)?;
+        match pressure {
+            Some(pressure) => {
+                self.pressures.insert(id, pressure);
+                self.replace_builtin_pressure(id);
+            }
+            None => {
+                self.pressures.remove(&id);
+            }
+        }
+        Ok(())
+    }
+
+    // The model replaces the pressure `bendy2d` applies itself, which would
+    // otherwise push on top of it
+    fn replace_builtin_pressure(&mut self, id: PolygonId) {
+        if let Some(polygon) = self
+            .polygons
+            .index(id.0)
+            .and_then(|index| self.solver.get_polygon_mut(index))
+        {
+            polygon.pressure = 0.0;
+        }
+    }
+
+    pub fn pressure(&self, id: PolygonId) -> Option<Pressure> {
+        self.pressures.get(&id).copied()
+    }
+
+    /// Scales the rest area of a polygon with pressure by `factor`, above 1
+    /// to inflate it and below to deflate it. Returns false if the polygon
+    /// has no pressure model to inflate.
+    pub fn inflate(&mut self, id: PolygonId, factor: f32) -> Result<bool, HandleError> {
+        self.polygon_index(id)?;
+        match self.pressures.get_mut(&id) {
+            Some(pressure) => {
+                pressure.rest_area *= factor;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Area enclosed by a polygon's vertices right now.
+    pub fn polygon_area(&self, id: PolygonId) -> Result<f32, HandleError> {
+        Ok(pressure::area(self.polygon(id)?))
+    }
+
+    fn match_shapes(&mut self) {
+        for (id, shape_match) in self.shape_matches.iter() {
+            if self.kinematic.contains_key(id) {
+                continue;
+            }
+            if let Some(polygon) = self
+                .polygons
+                .index(id.0)
+                .and_then(|index| self.solver.get_polygon_mut(index))
+            {
+                shape_match.solve(polygon);
+            }
+        }
+    }
+
+    /// Pulls a polygon back towards the shape it has now with `stiffness`,
+    /// see [`ShapeMatch`]. A polygon that already has shape matching keeps
+    /// its rest shape and only takes the new stiffness.
+    pub fn set_shape_match(&mut self, id: PolygonId, stiffness: f32) -> Result<(), HandleError> {
+        let shape_match = ShapeMatch::new(self.polygon(id)?, stiffness);
+        self.shape_matches
+            .entry(id)
+            .and_modify(|shape_match| shape_match.stiffness = stiffness)
+            .or_insert(shape_match);
+        Ok(())
+    }
+
+    pub fn clear_shape_match(&mut self, id: PolygonId) -> Result<(), HandleError> {
+        self.polygon_index(id)?;
+        self.shape_matches.remove(&id);
+        Ok(())
+    }
+
+    /// The shape matching stiffness of a polygon, None if it has none.
+    pub fn shape_match(&self, id: PolygonId) -> Option<f32> {
+        self.shape_matches
+            .get(&id)
+            .map(|shape_match| shape_match.stiffness)
+    }
+
+    /// The endpoint for a point in the solver's lists, the other way round
+    /// from [`World::resolve`].
+    pub fn endpoint(&self, point: PointRef) -> Option<Endpoint> {
+        match point {
+            PointRef::Particle { index } => self
+                .particle_id(index)
+                .map(|particle| Endpoint::Particle { particle }),
+            PointRef::Circle { index } => self
+                .circle_id(index)
+                .map(|circle| Endpoint::Circle { circle }),
+            PointRef::PolygonVertex { polygon, vertex } => self
+                .polygon_id(polygon)
+                .map(|polygon| Endpoint::PolygonVertex { polygon, vertex }),
+        }
+    }
+
+    /// Where an endpoint is in the solver right now, None if its body is
+    /// gone.
+    pub fn resolve(&self, endpoint: Endpoint) -> Option<Resolved> {
+        let point = match endpoint {
+            Endpoint::Particle { particle } => PointRef::Particle {
+                index: self.particle_index(particle).ok()?,
+            },
+            Endpoint::Circle { circle } => PointRef::Circle {
+                index: self.circle_index(circle).ok()?,
+            },
+            Endpoint::PolygonVertex { polygon, vertex } => {
+                let polygon = self.polygon_index(polygon).ok()?;
+                let vertices = self.solver.get_polygons().get(polygon)?.particles.len();
+                (vertex < vertices).then_some(PointRef::PolygonVertex { polygon, vertex })?
+            }
+            Endpoint::World { pos } => return Some(Resolved::Fixed(pos)),
+        };
+        Some(Resolved::Point(point))
+    }
+
+    /// Ties any two points together with a spring or link.
+    pub fn add_connector(&mut self, connector: &Connector) -> Result<ConnectorId, HandleError> {
+        for end in [connector.a, connector.b] {
+            match end {
+                Endpoint::Particle { particle } => {
+                    self.particle_index(particle)?;
+                }
+                Endpoint::Circle { circle } => {
+                    self.circle_index(circle)?;
+                }
+                Endpoint::PolygonVertex { polygon, vertex } => {
+                    if self.polygon(polygon)?.particles.len() <= vertex {
+                        return Err(HandleError::NoVertex(polygon, vertex));
+                    }
+                }
+                Endpoint::World { .. } => {}
+            }
+        }
+        self.connectors.push(*connector);
+        Ok(ConnectorId(self.connector_slots.insert()))
+    }
+
+    pub fn remove_connector(&mut self, id: ConnectorId) -> Result<(), HandleError> {
+        let index = self
+            .connector_slots
+            .index(id.0)
+            .ok_or(HandleError::StaleConnector(id))?;
+        self.connectors.swap_remove(index);
+        self.connector_slots.remove_index(index);
+        Ok(())
+    }
+
+    /// Mutable access for changing a connector in place. An end moved onto a
+    /// body that doesn't exist leaves the connector doing nothing.
+    pub fn connector_mut(&mut self, id: ConnectorId) -> Result<&mut Connector, HandleError> {
+        let index = self
+            .connector_slots
+            .index(id.0)
+            .ok_or(HandleError::StaleConnector(id))?;
+        Ok(&mut self.connectors[index])
+    }
+
+    pub fn connectors(&self) -> impl Iterator<Item = (ConnectorId, &Connector)> {
+        self.connector_slots
+            .ids
+            .iter()
+            .map(|id| ConnectorId(*id))
+            .zip(self.connectors.iter())
+    }
+
+    /// Where the two ends of a connector are now.
+    pub fn connector_ends(&self, connector: &Connector) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        let a = self.resolve(connector.a)?.pos(&self.solver)?;
+        let b = self.resolve(connector.b)?.pos(&self.solver)?;
+        Some((a, b))
+    }
+
+    /// Joins two polygons, or a polygon and the world, at the anchors of
+    /// `desc`. The polygons' current shapes are kept to tell how far they
+    /// turn from here on.
+    pub fn add_joint(&mut self, desc: &JointDesc) -> Result<JointId, HandleError> {
+        let joint = Joint::new(desc, |id| self.polygon(id))?;
+        self.joints.push(joint);
+        Ok(JointId(self.joint_slots.insert()))
+    }
+
+    pub fn remove_joint(&mut self, id: JointId) -> Result<(), HandleError> {
+        let index = self
+            .joint_slots
+            .index(id.0)
+            .ok_or(HandleError::StaleJoint(id))?;
+        self.joints.swap_remove(index);
+        self.joint_slots.remove_index(index);
+        Ok(())
+    }
+
+    pub fn joint(&self, id: JointId) -> Result<&Joint, HandleError> {
+        self.joint_slots
+            .index(id.0)
+            .and_then(|index| self.joints.get(index))
+            .ok_or(HandleError::StaleJoint(id))
+    }
+
+    pub fn joints(&self) -> impl Iterator<Item = (JointId, &Joint)> {
+        self.joint_slots
+            .ids
+            .iter()
+            .map(|id| JointId(*id))
+            .zip(self.joints.iter())
+    }
+
+    /// Where the two anchors of a joint are now.
+    pub fn joint_anchors(&self, joint: &Joint) -> Option<(Vector2<f32>, Vector2<f32>)> {
+        joint.anchors(&self.solver, |id| self.polygons.index(id.0))
+    }
+
+    pub fn remove_particle(&mut self, id: ParticleId) -> Result<(), HandleError> {
+        let index = self.particle_index(id)?;
+        self.remove(BodyRef::Particle { index });
+        Ok(())
+    }
+
+    pub fn remove_circle(&mut self, id: CircleId) -> Result<(), HandleError> {
+        let index = self.circle_index(id)?;
+        self.remove(BodyRef::Circle { index });
+        Ok(())
+    }
+
+    pub fn remove_polygon(&mut self, id: PolygonId) -> Result<(), HandleError> {
+        let index = self.polygon_index(id)?;
+        self.remove(BodyRef::Polygon { index });
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            snapshot: self.solver.snapshot(),
+            state: self.state(),
+        }
+    }
+
+    /// Puts back a snapshot, keeping the world's options like the combine
+    /// rules as they are now.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        self.solver.restore(&snapshot.snapshot);
+        self.set_state(&snapshot.state);
+    }
+
+    /// Everything but the solver, see [`WorldState`].
+    pub fn state(&self) -> WorldState {
+        WorldState {
+            particles: self.particles.clone(),
+            circles: self.circles.clone(),
+            polygons: self.polygons.clone(),
+            default_material: self.default_material,
+            materials: self.materials.clone(),
+            combine: self.combine,
+            filters: self.filters.clone(),
+            joints: self.joints.clone(),
+            joint_slots: self.joint_slots.clone(),
+            connectors: self.connectors.clone(),
+            connector_slots: self.connector_slots.clone(),
+            kinematic: self.kinematic.clone(),
+            shape_matches: self.shape_matches.clone(),
+            pressures: self.pressures.clone(),
+        }
+    }
+
+    // Everything in the state but the combine rules, which are an option
+    fn set_state(&mut self, state: &WorldState) {
+        self.particles = state.particles.clone();
+        self.circles = state.circles.clone();
+        self.polygons = state.polygons.clone();
+        self.default_material = state.default_material;
+        self.materials = state.materials.clone();
+        self.filters = state.filters.clone();
+        self.joints = state.joints.clone();
+        self.joint_slots = state.joint_slots.clone();
+        self.connectors = state.connectors.clone();
+        self.connector_slots = state.connector_slots.clone();
+        self.kinematic = state.kinematic.clone();
+        self.shape_matches = state.shape_matches.clone();
+        self.pressures = state.pressures.clone();
+        // A scene rebuilds its polygons with their built-in pressure
+        let ids: Vec<PolygonId> = self.pressures.keys().copied().collect();
+        for id in ids {
+            self.replace_builtin_pressure(id);
+        }
+        self.contacts.clear();
+        self.spring_breaks.clear();
+    }
+
+    /// Swaps in the solver of a scene, keeping the world's options and
+    /// listeners like [`World::reset`]. A scene saved with a world section
+    /// brings back the ids of its bodies, what is attached to them and the
+    /// combine rules, otherwise the bodies get fresh ids in list order.
+    pub fn load_scene(&mut self, scene: &Scene) {
+        self.reset(scene.build());
+        let Some(state) = &scene.world else {
+            return;
+        };
+        if state.fits(
+            self.get_particle_len(),
+            self.get_circles_len(),
+            self.get_polygons_len(),
+            self.get_static_lines().len(),
+        ) {
+            self.set_state(state);
+            self.combine = state.combine;
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bendy2d_testing::bench::{compare, Stats};
+
+#[test]
+fn stats_of_known_latencies() {
+    // 1 to 20 out of order
+    let latencies: Vec<f32> = (0..20).map(|i| ((i * 7) % 20 + 1) as f32).collect();
+    let stats = Stats::from_latencies(&latencies).unwrap();
+    assert_eq!(stats.samples, 20);
+    assert_eq!(stats.mean, 10.5);
+    // Nearest rank, the 10th, 19th and 20th of 20
+    assert_eq!(stats.median, 10.0);
+    assert_eq!(stats.p95, 19.0);
+    assert_eq!(stats.p99, 20.0);
+    assert_eq!(stats.max, 20.0);
+}
+
+#[test]
+fn stats_of_a_single_latency() {
+    let stats = Stats::from_latencies(&[0.004]).unwrap();
+    assert_eq!(stats.mean, 0.004);
+    assert_eq!(stats.median, 0.004);
+    assert_eq!(stats.p95, 0.004);
+    assert_eq!(stats.max, 0.004);
+}
+
+#[test]
+fn no_stats_without_latencies() {
+    assert!(Stats::from_latencies(&[]).is_none());
+}
+
+/// Stats with every metric at `seconds`.
+fn flat(seconds: f32) -> Stats {
+    Stats {
+        samples: 100,
+        mean: seconds,
+        median: seconds,
+        p95: seconds,
+        p99: seconds,
+        max: seconds,
+    }
+}
+
+#[test]
+fn slowdown_past_the_threshold_is_a_regression() {
+    let current = Stats {
+        p95: 2.5,
+        ..flat(2.0)
+    };
+    let regressions = compare(&flat(2.0), &current, 0.1);
+    assert_eq!(regressions.len(), 1);
+    assert_eq!(regressions[0].metric, "p95");
+    assert_eq!(regressions[0].baseline, 2.0);
+    assert_eq!(regressions[0].current, 2.5);
+    assert!((regressions[0].change() - 0.25).abs() < 1e-6);
+}
+
+#[test]
+fn slowdown_within_the_threshold_is_not_a_regression() {
+    assert!(compare(&flat(2.0), &flat(2.1), 0.1).is_empty());
+}
+
+#[test]
+fn speedup_is_not_a_regression() {
+    assert!(compare(&flat(2.0), &flat(1.0), 0.1).is_empty());
+}
+
+#[test]
+fn max_is_not_compared() {
+    let current = Stats {
+        max: 20.0,
+        ..flat(2.0)
+    };
+    assert!(compare(&flat(2.0), &current, 0.1).is_empty());
+}
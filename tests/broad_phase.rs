@@ -0,0 +1,75 @@
+use bendy2d_testing::broad_phase::{Aabb, BroadPhase};
+use nalgebra::Vector2;
+
+/// Deterministic boxes of mixed sizes, a third of them points like the
+/// boxes of free particles.
+fn boxes(count: usize) -> Vec<Aabb> {
+    let mut seed: u32 = 12345;
+    let mut random = move || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 8) as f32 / (1 << 24) as f32
+    };
+    (0..count)
+        .map(|i| {
+            let min = Vector2::new(random() * 500.0, random() * 500.0);
+            let size = if i % 3 == 0 {
+                Vector2::zeros()
+            } else {
+                Vector2::new(random() * 40.0, random() * 40.0)
+            };
+            Aabb {
+                min,
+                max: min + size,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn every_strategy_matches_brute_force() {
+    for count in [0, 1, 2, 50, 400] {
+        let aabbs = boxes(count);
+        let expected = BroadPhase::BruteForce.pairs(&aabbs);
+        for broad_phase in BroadPhase::ALL {
+            assert_eq!(
+                broad_phase.pairs(&aabbs),
+                expected,
+                "{} with {} boxes",
+                broad_phase.name(),
+                count
+            );
+        }
+    }
+}
+
+#[test]
+fn grid_handles_only_point_boxes() {
+    // With no box having a size, the cells fall back to the minimum size
+    // instead of collapsing
+    let aabbs: Vec<Aabb> = [(0.0, 0.0), (0.0, 0.0), (1000.0, 1000.0)]
+        .iter()
+        .map(|(x, y)| Aabb {
+            min: Vector2::new(*x, *y),
+            max: Vector2::new(*x, *y),
+        })
+        .collect();
+    assert_eq!(BroadPhase::Grid.pairs(&aabbs), vec![(0, 1)]);
+}
+
+#[test]
+fn grid_tests_oversized_boxes_against_everything() {
+    let mut aabbs = boxes(50);
+    // A body covering millions of cells, and one out at infinity
+    aabbs.push(Aabb {
+        min: Vector2::new(-1e9, -1e9),
+        max: Vector2::new(1e9, 1e9),
+    });
+    aabbs.push(Aabb {
+        min: Vector2::new(100.0, 100.0),
+        max: Vector2::new(f32::INFINITY, 120.0),
+    });
+    assert_eq!(
+        BroadPhase::Grid.pairs(&aabbs),
+        BroadPhase::BruteForce.pairs(&aabbs)
+    );
+}
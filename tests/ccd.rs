@@ -0,0 +1,38 @@
+use bendy2d_testing::ccd::{self, Edge};
+use bendy2d_testing::scenarios;
+use nalgebra::Vector2;
+
+#[test]
+fn high_speeds_do_not_tunnel() {
+    let mut scenario = scenarios::high_speeds();
+    if let Err(e) = ccd::check_swept(&mut scenario) {
+        panic!("{}", e);
+    }
+}
+
+/// An edge crossing the point's path at the start of the step that has
+/// collapsed to a point by the end of it.
+fn collapsing() -> Edge {
+    Edge {
+        start: [Vector2::new(-1.0, 0.0), Vector2::new(1.0, 0.0)],
+        end: [Vector2::zeros(), Vector2::zeros()],
+        polygon: None,
+    }
+}
+
+#[test]
+fn collapsed_edges_are_skipped() {
+    let (from, to) = (Vector2::new(0.0, -1.0), Vector2::new(0.0, 1.0));
+    assert_eq!(ccd::sweep(from, to, [collapsing()].iter(), &[]), None);
+
+    // A whole edge further along is still hit
+    let wall = Edge {
+        start: [Vector2::new(-1.0, 0.5), Vector2::new(1.0, 0.5)],
+        end: [Vector2::new(-1.0, 0.5), Vector2::new(1.0, 0.5)],
+        polygon: None,
+    };
+    let hit = ccd::sweep(from, to, [collapsing(), wall].iter(), &[]).unwrap();
+    assert!(hit.pos.x.is_finite() && hit.pos.y.is_finite());
+    assert!(hit.pos.y < 0.5);
+    assert_eq!(hit.normal, Vector2::new(0.0, -1.0));
+}
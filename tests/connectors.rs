@@ -0,0 +1,153 @@
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::connector::{Connector, ConnectorKind, Endpoint};
+use bendy2d_testing::pick::PointRef;
+use bendy2d_testing::replay::Action;
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::{HandleError, World};
+use nalgebra::Vector2;
+
+#[test]
+fn rope_hangs_a_balloon_from_the_world() {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::new(0.0, 980.0);
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    let balloon = world.add_polygon(
+        PolygonShape::PressureCircle {
+            radius: 30.0,
+            pos: Vector2::new(0.0, 130.0),
+            point_count: 12,
+            fixed: false,
+            stiffness: 1000.0,
+            pressure: 1.0,
+        }
+        .build(),
+    );
+    let top = world.polygon(balloon).unwrap().particles[0].pos;
+
+    // World anchor - particle - particle - balloon vertex
+    let anchor = Endpoint::World {
+        pos: Vector2::zeros(),
+    };
+    let rope: Vec<Endpoint> = [30.0, 60.0]
+        .iter()
+        .map(|y| Endpoint::Particle {
+            particle: world.add_particle(Vector2::new(0.0, *y)),
+        })
+        .collect();
+    let vertex = Endpoint::PolygonVertex {
+        polygon: balloon,
+        vertex: 0,
+    };
+    let ends = [anchor, rope[0], rope[1], vertex];
+    for pair in ends.windows(2) {
+        let connector = Connector {
+            a: pair[0],
+            b: pair[1],
+            kind: ConnectorKind::Link {
+                target_distance: 30.0,
+            },
+        };
+        world.add_connector(&connector).unwrap();
+    }
+
+    for _ in 0..300 {
+        world.update(0.01);
+    }
+    for (_, connector) in world.connectors() {
+        let (a, b) = world.connector_ends(connector).unwrap();
+        let length = (b - a).magnitude();
+        assert!(
+            (length - 30.0).abs() < 3.0,
+            "link stretched to {} (balloon started at {:?})",
+            length,
+            top
+        );
+    }
+}
+
+#[test]
+fn connectors_need_existing_ends() {
+    let mut world = World::new();
+    let polygon = world.add_polygon(
+        PolygonShape::Circle {
+            radius: 10.0,
+            pos: Vector2::zeros(),
+            point_count: 4,
+            fixed: false,
+            stiffness: 100.0,
+            permanence_threshold: -1.0,
+        }
+        .build(),
+    );
+    let connector = Connector {
+        a: Endpoint::PolygonVertex { polygon, vertex: 4 },
+        b: Endpoint::World {
+            pos: Vector2::zeros(),
+        },
+        kind: ConnectorKind::Link {
+            target_distance: 1.0,
+        },
+    };
+    assert_eq!(
+        world.add_connector(&connector).unwrap_err(),
+        HandleError::NoVertex(polygon, 4)
+    );
+
+    world.remove_polygon(polygon).unwrap();
+    assert_eq!(
+        world.add_connector(&connector).unwrap_err(),
+        HandleError::StalePolygon(polygon)
+    );
+}
+
+#[test]
+fn grab_spring_follows_the_mouse_until_removed() {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::zeros();
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    world.add_particle(Vector2::zeros());
+    let point = PointRef::Particle { index: 0 };
+    let grab = world
+        .add_connector(&Connector {
+            a: world.endpoint(point).unwrap(),
+            b: Endpoint::World {
+                pos: Vector2::zeros(),
+            },
+            kind: ConnectorKind::Spring {
+                rest_length: 0.0,
+                stiffness: 5000.0,
+            },
+        })
+        .unwrap();
+
+    for _ in 0..100 {
+        Action::MoveConnectorEnd {
+            connector: grab,
+            pos: Vector2::new(100.0, 0.0),
+        }
+        .apply(&mut world)
+        .unwrap();
+        world.update(0.01);
+    }
+    let pos = world.get_particles()[0].pos;
+    assert!((pos.x - 100.0).abs() < 10.0, "particle at {:?}", pos);
+
+    world.remove_connector(grab).unwrap();
+    assert_eq!(
+        Action::MoveConnectorEnd {
+            connector: grab,
+            pos: Vector2::zeros(),
+        }
+        .apply(&mut world),
+        Err(HandleError::StaleConnector(grab))
+    );
+    assert_eq!(world.connectors().count(), 0);
+}
@@ -0,0 +1,64 @@
+use bendy2d_testing::events::{ContactEvent, ContactPhase};
+use bendy2d_testing::scenarios;
+use bendy2d_testing::world::World;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+#[test]
+fn contact_events_follow_begin_persist_end() {
+    let scenario = scenarios::deterministic();
+    let mut world = World::from_solver(scenario.solver);
+    let heard: Rc<RefCell<Vec<ContactEvent>>> = Rc::default();
+    let listener = heard.clone();
+    world.on_contact(move |event| listener.borrow_mut().push(*event));
+
+    let mut touching = BTreeSet::new();
+    let mut begins = 0;
+    for step in 0..scenario.steps {
+        world.update(scenario.dt);
+        let events: Vec<ContactEvent> = heard.borrow_mut().drain(..).collect();
+        for event in events.iter() {
+            let pair = (event.a, event.b);
+            assert!(event.a < event.b, "step {}: pair out of order", step);
+            match event.phase {
+                ContactPhase::Begin => {
+                    assert!(
+                        touching.insert(pair),
+                        "step {}: {:?} began twice",
+                        step,
+                        pair
+                    );
+                    begins += 1;
+                }
+                ContactPhase::Persist => {
+                    assert!(
+                        touching.contains(&pair),
+                        "step {}: {:?} persisted without beginning",
+                        step,
+                        pair
+                    );
+                }
+                ContactPhase::End => {
+                    assert!(
+                        touching.remove(&pair),
+                        "step {}: {:?} ended without beginning",
+                        step,
+                        pair
+                    );
+                }
+            }
+            if event.phase != ContactPhase::End {
+                assert!((event.normal.magnitude() - 1.0).abs() < 1e-3);
+                assert!(event.impulse >= 0.0);
+            }
+        }
+        let current: BTreeSet<_> = world.contacts().map(|c| (c.a, c.b)).collect();
+        assert_eq!(
+            current, touching,
+            "step {}: contacts() disagrees with the events",
+            step
+        );
+    }
+    assert!(begins > 0, "nothing touched in the deterministic scenario");
+}
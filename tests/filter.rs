@@ -0,0 +1,170 @@
+use bendy2d::circle::Circle;
+use bendy2d::particle::Particle;
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::filter::CollisionFilter;
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::{BodyId, World};
+use nalgebra::Vector2;
+
+/// A world without gravity, large enough that nothing reaches the bounds.
+fn world() -> World {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::zeros();
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    World::from_solver(solver)
+}
+
+fn on_layer(layer: u32) -> CollisionFilter {
+    CollisionFilter {
+        layers: 1 << layer,
+        mask: 1 << layer,
+        group: 0,
+    }
+}
+
+/// Gives every particle a velocity of `velocity` per step along x.
+fn launch<'a>(particles: impl Iterator<Item = &'a mut Particle>, velocity: f32) {
+    for particle in particles {
+        particle.prev_pos = particle.pos - Vector2::new(velocity, 0.0);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Particle,
+    Circle,
+    Box,
+    StaticLine,
+}
+
+/// A body of `kind` at `x`, moving by `velocity` per step. Static lines
+/// stand still across the x axis.
+fn add(world: &mut World, kind: Kind, x: f32, velocity: f32, filter: CollisionFilter) -> BodyId {
+    let pos = Vector2::new(x, 0.0);
+    let body = match kind {
+        Kind::Particle => {
+            let id = world.add_particle(pos);
+            let index = world.particle_index(id).unwrap();
+            launch(
+                world.solver_mut().get_particle_mut(index).into_iter(),
+                velocity,
+            );
+            BodyId::Particle(id)
+        }
+        Kind::Circle => {
+            let mut point = Particle::new(pos);
+            launch(std::iter::once(&mut point), velocity);
+            BodyId::Circle(world.add_circle(Circle {
+                point,
+                radius: 20.0,
+            }))
+        }
+        Kind::Box => {
+            let mut polygon = PolygonShape::Box {
+                pos,
+                rotation: 0.0,
+                size: Vector2::new(40.0, 40.0),
+                mass: 1.0,
+                stiffness: 1000.0,
+                fixed: false,
+                permanence_threshold: -1.0,
+            }
+            .build();
+            launch(polygon.particles.iter_mut(), velocity);
+            BodyId::Polygon(world.add_polygon(polygon))
+        }
+        Kind::StaticLine => {
+            world.add_static_line((Vector2::new(x, -100.0), Vector2::new(x, 100.0)));
+            BodyId::StaticLine(world.get_static_lines().len() - 1)
+        }
+    };
+    let index = world.body_ref(body).unwrap();
+    assert!(world.set_collision_filter(index, filter));
+    body
+}
+
+fn x(world: &World, body: BodyId) -> f32 {
+    match body {
+        BodyId::Particle(id) => world.particle(id).unwrap().pos.x,
+        BodyId::Circle(id) => world.circle(id).unwrap().point.pos.x,
+        BodyId::Polygon(id) => world.polygon(id).unwrap().center.x,
+        BodyId::StaticLine(index) => world.get_static_lines()[index].0.x,
+    }
+}
+
+/// Sends an `a` and a `b` at each other head on, returning whether they end
+/// up on the other side of each other.
+fn passes(a: Kind, b: Kind, filter_a: CollisionFilter, filter_b: CollisionFilter) -> bool {
+    let mut world = world();
+    let a = add(&mut world, a, -60.0, 1.0, filter_a);
+    let b = match b {
+        Kind::StaticLine => add(&mut world, b, 0.0, 0.0, filter_b),
+        _ => add(&mut world, b, 60.0, -1.0, filter_b),
+    };
+    for _ in 0..300 {
+        world.update(0.01);
+    }
+    x(&world, a) > x(&world, b)
+}
+
+fn boxes_pass(a: CollisionFilter, b: CollisionFilter) -> bool {
+    passes(Kind::Box, Kind::Box, a, b)
+}
+
+fn circles_pass(a: CollisionFilter, b: CollisionFilter) -> bool {
+    passes(Kind::Circle, Kind::Circle, a, b)
+}
+
+/// Every pair of body kinds the solver collides.
+const PAIRS: [(Kind, Kind); 8] = [
+    (Kind::Box, Kind::Box),
+    (Kind::Box, Kind::StaticLine),
+    (Kind::Circle, Kind::Circle),
+    (Kind::Circle, Kind::Box),
+    (Kind::Circle, Kind::StaticLine),
+    (Kind::Particle, Kind::Circle),
+    (Kind::Particle, Kind::Box),
+    (Kind::Particle, Kind::StaticLine),
+];
+
+#[test]
+fn disjoint_layers_pass_through() {
+    assert!(boxes_pass(on_layer(0), on_layer(1)));
+    assert!(circles_pass(on_layer(0), on_layer(1)));
+}
+
+#[test]
+fn same_layer_collides() {
+    assert!(!boxes_pass(on_layer(2), on_layer(2)));
+    assert!(!circles_pass(on_layer(2), on_layer(2)));
+}
+
+#[test]
+fn shared_group_passes_through() {
+    let group = CollisionFilter {
+        group: 7,
+        ..CollisionFilter::default()
+    };
+    assert!(boxes_pass(group, group));
+}
+
+#[test]
+fn every_pair_of_body_kinds_is_filtered() {
+    for (a, b) in PAIRS {
+        assert!(
+            !passes(a, b, on_layer(3), on_layer(3)),
+            "{:?} and {:?}",
+            a,
+            b
+        );
+        assert!(
+            passes(a, b, on_layer(3), on_layer(4)),
+            "{:?} and {:?}",
+            a,
+            b
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use bendy2d_testing::golden::{self, Frame, GoldenError, Tolerance, Trajectory};
+use nalgebra::Vector2;
+
+#[test]
+fn golden_trajectories() {
+    let failures: Vec<String> = golden::SCENARIOS
+        .iter()
+        .filter_map(|(name, _)| {
+            golden::check(name, Tolerance::default())
+                .err()
+                .map(|e| format!("{}: {}", name, e))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn short_frames_are_a_layout_error() {
+    let trajectory = |positions: usize| Trajectory {
+        version: golden::FORMAT_VERSION,
+        scenario: "test".to_string(),
+        dt: 0.01,
+        steps: 1,
+        polygons: vec![3],
+        frames: vec![Frame {
+            frame: 0,
+            positions: vec![Vector2::zeros(); positions],
+        }],
+    };
+    let result = golden::compare(&trajectory(3), &trajectory(2), Tolerance::default());
+    assert!(
+        matches!(result, Err(GoldenError::Layout(_))),
+        "{:?}",
+        result
+    );
+    assert!(golden::compare(&trajectory(3), &trajectory(3), Tolerance::exact()).is_ok());
+}
@@ -0,0 +1,79 @@
+use bendy2d::circle::Circle;
+use bendy2d::particle::Particle;
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::{HandleError, World};
+use nalgebra::Vector2;
+
+fn circle(x: f32) -> Circle {
+    Circle {
+        point: Particle::new(Vector2::new(x, 0.0)),
+        radius: 10.0,
+    }
+}
+
+fn square(x: f32) -> PolygonShape {
+    PolygonShape::Box {
+        pos: Vector2::new(x, 0.0),
+        rotation: 0.0,
+        size: Vector2::new(20.0, 20.0),
+        mass: 1.0,
+        stiffness: 1000.0,
+        fixed: false,
+        permanence_threshold: -1.0,
+    }
+}
+
+#[test]
+fn removed_particle_handle_is_stale_after_its_slot_is_reused() {
+    let mut world = World::new();
+    let old = world.add_particle(Vector2::new(1.0, 0.0));
+    let kept = world.add_particle(Vector2::new(2.0, 0.0));
+    world.remove_particle(old).unwrap();
+    let new = world.add_particle(Vector2::new(3.0, 0.0));
+
+    assert_ne!(old, new);
+    assert_eq!(
+        world.particle(old).err(),
+        Some(HandleError::StaleParticle(old))
+    );
+    assert_eq!(
+        world.remove_particle(old),
+        Err(HandleError::StaleParticle(old))
+    );
+    assert_eq!(world.particle(kept).unwrap().pos.x, 2.0);
+    assert_eq!(world.particle(new).unwrap().pos.x, 3.0);
+}
+
+#[test]
+fn removed_circle_handle_is_stale_after_its_slot_is_reused() {
+    let mut world = World::new();
+    let old = world.add_circle(circle(1.0));
+    world.remove_circle(old).unwrap();
+    let new = world.add_circle(circle(3.0));
+
+    assert_ne!(old, new);
+    assert_eq!(world.circle(old).err(), Some(HandleError::StaleCircle(old)));
+    assert_eq!(world.circle_index(new), Ok(0));
+    assert_eq!(world.circle(new).unwrap().point.pos.x, 3.0);
+}
+
+#[test]
+fn removed_polygon_handle_is_stale_after_its_slot_is_reused() {
+    let mut world = World::new();
+    let old = world.add_polygon(square(0.0).build());
+    world.remove_polygon(old).unwrap();
+    let new = world.add_polygon(square(100.0).build());
+
+    assert_ne!(old, new);
+    assert_eq!(
+        world.polygon(old).err(),
+        Some(HandleError::StalePolygon(old))
+    );
+    assert_eq!(
+        world.polygon_mut(old).err(),
+        Some(HandleError::StalePolygon(old))
+    );
+    assert_eq!(world.polygon_index(new), Ok(0));
+    let x = world.polygon(new).unwrap().particles[0].pos.x;
+    assert!(x > 50.0);
+}
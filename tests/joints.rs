@@ -0,0 +1,102 @@
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::joint::{Anchor, JointDesc, JointKind};
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::World;
+use nalgebra::Vector2;
+
+/// Two boxes hanging in a large empty world under gravity.
+fn world() -> World {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::new(0.0, 980.0);
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    for x in [0.0, 100.0] {
+        world.add_polygon(
+            PolygonShape::Box {
+                pos: Vector2::new(x, 0.0),
+                rotation: 0.0,
+                size: Vector2::new(40.0, 40.0),
+                mass: 1.0,
+                stiffness: 1000.0,
+                fixed: false,
+                permanence_threshold: -1.0,
+            }
+            .build(),
+        );
+    }
+    world
+}
+
+/// Largest distance between the anchors of any joint over 200 steps.
+fn max_gap(world: &mut World) -> f32 {
+    let mut gap: f32 = 0.0;
+    for _ in 0..200 {
+        world.update(0.01);
+        for (_, joint) in world.joints() {
+            let (a, b) = world.joint_anchors(joint).expect("both polygons exist");
+            gap = gap.max((a - b).magnitude());
+        }
+    }
+    gap
+}
+
+#[test]
+fn revolute_joints_hold_a_chain_to_the_world() {
+    let mut world = world();
+    let first = world.polygon_id(0).unwrap();
+    let second = world.polygon_id(1).unwrap();
+    let revolute = JointKind::Revolute { limits: None };
+    world
+        .add_joint(&JointDesc {
+            a: Anchor::World {
+                pos: Vector2::new(0.0, -20.0),
+            },
+            b: Anchor::Polygon {
+                polygon: first,
+                pos: Vector2::new(0.0, -20.0),
+            },
+            kind: revolute,
+        })
+        .unwrap();
+    world
+        .add_joint(&JointDesc {
+            a: Anchor::Polygon {
+                polygon: first,
+                pos: Vector2::new(50.0, 0.0),
+            },
+            b: Anchor::Polygon {
+                polygon: second,
+                pos: Vector2::new(50.0, 0.0),
+            },
+            kind: revolute,
+        })
+        .unwrap();
+
+    let gap = max_gap(&mut world);
+    assert!(gap < 5.0, "joint anchors drifted {} apart", gap);
+    // The chain hangs instead of falling
+    assert!(world.polygon(second).unwrap().center.y < 200.0);
+}
+
+#[test]
+fn removing_a_polygon_removes_its_joints() {
+    let mut world = world();
+    let first = world.polygon_id(0).unwrap();
+    world
+        .add_joint(&JointDesc {
+            a: Anchor::World {
+                pos: Vector2::zeros(),
+            },
+            b: Anchor::Polygon {
+                polygon: first,
+                pos: Vector2::zeros(),
+            },
+            kind: JointKind::Weld,
+        })
+        .unwrap();
+    world.remove_polygon(first).unwrap();
+    assert_eq!(world.joints().count(), 0);
+}
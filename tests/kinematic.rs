@@ -0,0 +1,96 @@
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::kinematic::{Motion, Transform};
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::World;
+use nalgebra::Vector2;
+
+fn world() -> World {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::new(0.0, 980.0);
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    World::from_solver(solver)
+}
+
+fn circle(pos: Vector2<f32>, radius: f32) -> PolygonShape {
+    PolygonShape::PressureCircle {
+        radius,
+        pos,
+        point_count: 12,
+        fixed: false,
+        stiffness: 1000.0,
+        pressure: 1.0,
+    }
+}
+
+#[test]
+fn fixed_polygon_ignores_gravity() {
+    let mut world = world();
+    let polygon = world.add_polygon(circle(Vector2::zeros(), 30.0).build());
+    let start: Vec<_> = world
+        .polygon(polygon)
+        .unwrap()
+        .particles
+        .iter()
+        .map(|p| p.pos)
+        .collect();
+    world.set_kinematic(polygon, Motion::Fixed).unwrap();
+
+    for _ in 0..100 {
+        world.update(0.01);
+    }
+    let end = &world.polygon(polygon).unwrap().particles;
+    for (a, b) in start.iter().zip(end.iter()) {
+        assert!((a - b.pos).magnitude() < 1e-3, "{} moved to {}", a, b.pos);
+    }
+}
+
+#[test]
+fn platform_carries_a_resting_body() {
+    let mut world = world();
+    let platform = world.add_polygon(circle(Vector2::zeros(), 100.0).build());
+    let rider = world.add_polygon(circle(Vector2::new(0.0, -140.0), 30.0).build());
+    world
+        .set_kinematic(
+            platform,
+            Motion::Linear {
+                velocity: Vector2::new(0.0, -50.0),
+            },
+        )
+        .unwrap();
+
+    for _ in 0..200 {
+        world.update(0.01);
+    }
+    // The platform rose by 100 and the rider was pushed up with it instead
+    // of falling through
+    let platform = world.polygon(platform).unwrap().center;
+    let rider = world.polygon(rider).unwrap().center;
+    assert!((platform.y + 100.0).abs() < 1.0, "platform at {}", platform);
+    assert!(rider.y < platform.y - 100.0, "rider at {}", rider);
+}
+
+#[test]
+fn manual_transform_moves_with_velocity() {
+    let mut world = world();
+    let polygon = world.add_polygon(circle(Vector2::zeros(), 30.0).build());
+    for step in 0..5 {
+        let transform = Transform {
+            translation: Vector2::new(2.0 * step as f32, 0.0),
+            rotation: 0.0,
+        };
+        world.set_kinematic_transform(polygon, transform).unwrap();
+        world.update(0.01);
+    }
+    // Each vertex came from where the previous transform put it
+    for particle in world.polygon(polygon).unwrap().particles.iter() {
+        let velocity = particle.pos - particle.prev_pos;
+        assert!(
+            (velocity - Vector2::new(2.0, 0.0)).magnitude() < 1e-3,
+            "moved by {}",
+            velocity
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use bendy2d::circle::Circle;
+use bendy2d::particle::Particle;
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::material::Material;
+use bendy2d_testing::pick::BodyRef;
+use bendy2d_testing::world::World;
+use nalgebra::Vector2;
+
+fn world(gravity: Vector2<f32>) -> World {
+    let mut solver = Solver::new();
+    solver.gravity = gravity;
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    World::from_solver(solver)
+}
+
+/// A circle at `pos` moving by `velocity` per step.
+fn circle(pos: Vector2<f32>, velocity: Vector2<f32>) -> Circle {
+    let mut point = Particle::new(pos);
+    point.prev_pos = pos - velocity;
+    Circle {
+        point,
+        radius: 10.0,
+    }
+}
+
+/// Highest a circle dropped onto a line from 200 above gets once it has
+/// hit the line, with both given `restitution`.
+fn rebound(restitution: f32) -> f32 {
+    let mut world = world(Vector2::new(0.0, 980.0));
+    world.add_static_line((Vector2::new(-100.0, 0.0), Vector2::new(100.0, 0.0)));
+    world.add_circle(circle(Vector2::new(0.0, -200.0), Vector2::zeros()));
+    let material = Material {
+        static_friction: 0.0,
+        dynamic_friction: 0.0,
+        restitution,
+    };
+    world.set_material(BodyRef::Circle { index: 0 }, material);
+    world.set_material(BodyRef::StaticLine { index: 0 }, material);
+
+    let mut hit = false;
+    let mut highest = f32::MAX;
+    for _ in 0..300 {
+        world.update(0.01);
+        let y = world.get_circles()[0].point.pos.y;
+        hit |= y > -15.0;
+        if hit {
+            highest = highest.min(y);
+        }
+    }
+    assert!(hit, "the circle never reached the line");
+    -highest
+}
+
+#[test]
+fn bouncy_circle_keeps_more_height() {
+    let bouncy = rebound(0.9);
+    let dead = rebound(0.0);
+    assert!(bouncy > dead + 50.0, "bouncy {} dead {}", bouncy, dead);
+}
+
+#[test]
+fn circle_friction_is_equal_and_opposite() {
+    let mut world = world(Vector2::zeros());
+    let moving = Vector2::new(1.0, 2.0);
+    world.add_circle(circle(Vector2::new(-10.5, 0.0), moving));
+    world.add_circle(circle(Vector2::new(10.5, 0.0), Vector2::zeros()));
+    let rough = Material {
+        static_friction: 1.0,
+        dynamic_friction: 0.5,
+        restitution: 0.5,
+    };
+    world.set_material(BodyRef::Circle { index: 0 }, rough);
+    world.set_material(BodyRef::Circle { index: 1 }, rough);
+
+    for _ in 0..5 {
+        world.update(0.01);
+    }
+    let velocity = |index: usize| {
+        let point = &world.get_circles()[index].point;
+        point.pos - point.prev_pos
+    };
+    // Whatever one circle lost the other gained
+    let total = velocity(0) + velocity(1);
+    assert!((total - moving).magnitude() < 1e-3, "total {}", total);
+    // and friction dragged the resting one along
+    assert!(velocity(1).y > 0.1, "dragged at {}", velocity(1));
+}
@@ -0,0 +1,104 @@
+use bendy2d_testing::outline::{
+    is_simple, mesh_springs, polygon, signed_area, triangulate, OutlineError,
+};
+use nalgebra::Vector2;
+use std::f32::consts::PI;
+
+fn l_shape() -> Vec<Vector2<f32>> {
+    [
+        (0.0, 0.0),
+        (30.0, 0.0),
+        (30.0, 10.0),
+        (10.0, 10.0),
+        (10.0, 40.0),
+        (0.0, 40.0),
+    ]
+    .iter()
+    .map(|(x, y)| Vector2::new(*x, *y))
+    .collect()
+}
+
+fn star(points: usize) -> Vec<Vector2<f32>> {
+    (0..points * 2)
+        .map(|i| {
+            let angle = i as f32 * PI / points as f32;
+            let radius = if i % 2 == 0 { 50.0 } else { 20.0 };
+            Vector2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// Checks the triangles cover exactly the outline's area with no flipped
+/// or empty triangle.
+fn check_cover(outline: &[Vector2<f32>]) {
+    let triangles = triangulate(outline).unwrap();
+    assert_eq!(triangles.len(), outline.len() - 2);
+    let area = signed_area(outline);
+    let mut total = 0.0;
+    for triangle in triangles.iter() {
+        let points = triangle.map(|index| outline[index]);
+        let triangle_area = signed_area(&points);
+        assert!(
+            triangle_area * area > 0.0,
+            "triangle {:?} is flipped",
+            triangle
+        );
+        total += triangle_area;
+    }
+    assert!((total - area).abs() < 1e-2, "{} != {}", total, area);
+}
+
+#[test]
+fn concave_outlines_are_covered() {
+    check_cover(&l_shape());
+    check_cover(&star(5));
+
+    let mut reversed = l_shape();
+    reversed.reverse();
+    check_cover(&reversed);
+}
+
+#[test]
+fn invalid_outlines_are_rejected() {
+    let two = &l_shape()[..2];
+    assert_eq!(triangulate(two), Err(OutlineError::TooFewPoints));
+
+    let line = [
+        Vector2::new(0.0, 0.0),
+        Vector2::new(10.0, 0.0),
+        Vector2::new(20.0, 0.0),
+    ];
+    assert_eq!(triangulate(&line), Err(OutlineError::Degenerate));
+
+    let bowtie = [
+        Vector2::new(0.0, 0.0),
+        Vector2::new(10.0, 10.0),
+        Vector2::new(10.0, 0.0),
+        Vector2::new(0.0, 10.0),
+    ];
+    assert!(!is_simple(&bowtie));
+    assert_eq!(triangulate(&bowtie), Err(OutlineError::SelfIntersecting));
+}
+
+#[test]
+fn polygon_is_built_from_the_outline() {
+    let mut outline = l_shape();
+    if signed_area(&outline) > 0.0 {
+        outline.reverse();
+    }
+    let polygon = polygon(&outline, false, 100.0, -1.0).unwrap();
+
+    let points: Vec<Vector2<f32>> = polygon.particles.iter().map(|p| p.pos).collect();
+    assert!(signed_area(&points) > 0.0);
+    let mut reversed = outline.clone();
+    reversed.reverse();
+    assert_eq!(points, reversed);
+
+    let triangles = triangulate(&points).unwrap();
+    let springs = mesh_springs(&points, &triangles, 100.0, -1.0);
+    assert_eq!(polygon.particle_springs.len(), springs.len());
+    for spring in polygon.particle_springs.iter() {
+        let length = (points[spring.particle_b] - points[spring.particle_a]).magnitude();
+        assert!((spring.rest_length - length).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,146 @@
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::connector::Endpoint;
+use bendy2d_testing::plasticity::{strain, yield_strain, yielded};
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::{ParticleId, Spring, World};
+use nalgebra::Vector2;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Two particles 10 apart joined by a spring, with no gravity.
+fn world(permanence_threshold: f32) -> (World, ParticleId, ParticleId) {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::zeros();
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    let a = world.add_particle(Vector2::zeros());
+    let b = world.add_particle(Vector2::new(10.0, 0.0));
+    world
+        .add_particle_spring(&Spring {
+            particle_a: a,
+            particle_b: b,
+            rest_length: 10.0,
+            stiffness: 1.0,
+            permanence_threshold,
+        })
+        .unwrap();
+    (world, a, b)
+}
+
+/// Teleports `b` to `x` with no velocity.
+fn pull(world: &mut World, b: ParticleId, x: f32) {
+    let index = world.particle_index(b).unwrap();
+    let particle = world.solver_mut().get_particle_mut(index).unwrap();
+    particle.pos = Vector2::new(x, 0.0);
+    particle.prev_pos = particle.pos;
+}
+
+#[test]
+fn yield_leaves_the_threshold_strain() {
+    assert_eq!(yielded(10.5, 10.0, -0.1), None);
+    assert_eq!(yielded(20.0, 10.0, -1.0), None);
+
+    let stretched = yielded(20.0, 10.0, -0.1).unwrap();
+    assert!((strain(20.0, stretched) - 0.1).abs() < 1e-5);
+    let compressed = yielded(5.0, 10.0, -0.1).unwrap();
+    assert!((strain(5.0, compressed) + 0.1).abs() < 1e-5);
+}
+
+#[test]
+fn threshold_runs_from_fully_plastic_to_never() {
+    assert_eq!(yield_strain(0.0), Some(0.0));
+    assert_eq!(yield_strain(-0.25), Some(0.25));
+    assert_eq!(yield_strain(-1.0), None);
+    assert_eq!(yield_strain(-2.0), None);
+    // At 0 the spring takes whatever length it is held at
+    assert_eq!(yielded(12.0, 10.0, 0.0), Some(12.0));
+}
+
+#[test]
+fn overstretched_spring_keeps_a_longer_rest_length() {
+    let (mut world, _, b) = world(-0.1);
+    pull(&mut world, b, 30.0);
+    world.step();
+
+    let spring = &world.get_particle_springs()[0];
+    assert!(
+        spring.rest_length > 20.0,
+        "rest length {}",
+        spring.rest_length
+    );
+}
+
+#[test]
+fn spring_breaks_past_the_break_strain() {
+    let (mut world, a, b) = world(-1.0);
+    world.set_break_strain(Some(1.0));
+    let breaks = Rc::new(RefCell::new(Vec::new()));
+    let heard = breaks.clone();
+    world.on_spring_break(move |event| heard.borrow_mut().push(*event));
+
+    // Stretched by half: still holds
+    pull(&mut world, b, 15.0);
+    world.step();
+    assert_eq!(world.get_particle_springs().len(), 1);
+
+    pull(&mut world, b, 40.0);
+    world.step();
+    assert!(world.get_particle_springs().is_empty());
+    assert_eq!(world.spring_breaks().len(), 1);
+    let event = breaks.borrow()[0];
+    assert_eq!(
+        (event.a, event.b),
+        (
+            Endpoint::Particle { particle: a },
+            Endpoint::Particle { particle: b }
+        )
+    );
+    assert_eq!(event.rest_length, 10.0);
+}
+
+#[test]
+fn particle_springs_yield_every_update() {
+    let (mut world, a, b) = world(-0.1);
+    pull(&mut world, b, 12.0);
+    world.update(0.01);
+    let length = (world.particle(b).unwrap().pos - world.particle(a).unwrap().pos).magnitude();
+    let rest_length = world.get_particle_springs()[0].rest_length;
+    assert!((strain(length, rest_length) - 0.1).abs() < 1e-4);
+}
+
+#[test]
+fn polygon_springs_break_past_the_break_strain() {
+    let (mut world, _, _) = world(-1.0);
+    let polygon = world.add_polygon(
+        PolygonShape::Box {
+            pos: Vector2::new(0.0, 100.0),
+            rotation: 0.0,
+            size: Vector2::new(20.0, 20.0),
+            mass: 1.0,
+            stiffness: 1.0,
+            fixed: false,
+            permanence_threshold: -1.0,
+        }
+        .build(),
+    );
+    world.set_break_strain(Some(1.0));
+    let index = world.polygon_index(polygon).unwrap();
+    let springs = world.get_polygons()[index].particle_springs.len();
+
+    // Drag one vertex far out, taking every spring on it past the limit
+    let vertex = &mut world.solver_mut().get_polygon_mut(index).unwrap().particles[0];
+    vertex.pos += Vector2::new(500.0, 0.0);
+    vertex.prev_pos = vertex.pos;
+    world.update(0.01);
+
+    let left = world.get_polygons()[index].particle_springs.len();
+    assert!(left < springs, "{} of {} springs left", left, springs);
+    assert_eq!(world.spring_breaks().len(), springs - left);
+    for event in world.spring_breaks() {
+        let ends = [event.a, event.b];
+        assert!(ends.contains(&Endpoint::PolygonVertex { polygon, vertex: 0 }));
+    }
+}
@@ -0,0 +1,112 @@
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::pressure::{area, Pressure, PressureModel};
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::world::World;
+use nalgebra::Vector2;
+
+fn square(size: f32) -> Polygon {
+    PolygonShape::Box {
+        pos: Vector2::zeros(),
+        rotation: 0.0,
+        size: Vector2::new(size, size),
+        mass: 1.0,
+        stiffness: 1000.0,
+        fixed: false,
+        permanence_threshold: -1.0,
+    }
+    .build()
+}
+
+#[test]
+fn ideal_gas_pressure_follows_the_area() {
+    let pressure = Pressure {
+        model: PressureModel::IdealGas { pressure: 100.0 },
+        rest_area: 400.0,
+    };
+    assert_eq!(pressure.pressure(400.0), 100.0);
+    assert_eq!(pressure.pressure(200.0), 200.0);
+    assert_eq!(pressure.pressure(800.0), 50.0);
+}
+
+#[test]
+fn gas_pushes_the_outline_out() {
+    for model in [
+        PressureModel::Constant { pressure: 100.0 },
+        PressureModel::IdealGas { pressure: 100.0 },
+    ] {
+        let mut polygon = square(40.0);
+        let pressure = Pressure::new(&polygon, model);
+        pressure.apply(&mut polygon, 0.1);
+        assert!(area(&polygon) > pressure.rest_area, "{:?}", model);
+    }
+}
+
+#[test]
+fn target_area_restores_a_squashed_polygon() {
+    let mut polygon = square(40.0);
+    let pressure = Pressure::new(&polygon, PressureModel::TargetArea { stiffness: 1.0 });
+    for particle in polygon.particles.iter_mut() {
+        particle.pos *= 0.5;
+    }
+    for _ in 0..5 {
+        pressure.apply(&mut polygon, 0.01);
+    }
+    let restored = area(&polygon);
+    assert!(
+        (restored - pressure.rest_area).abs() < 0.01 * pressure.rest_area,
+        "area {} for {} at rest",
+        restored,
+        pressure.rest_area
+    );
+}
+
+#[test]
+fn inflating_scales_the_rest_area() {
+    let mut solver = Solver::new();
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    let id = world.add_polygon(square(40.0));
+    world
+        .set_pressure_model(id, PressureModel::TargetArea { stiffness: 0.5 })
+        .unwrap();
+    let rest_area = world.pressure(id).unwrap().rest_area;
+    assert!((rest_area - world.polygon_area(id).unwrap()).abs() < 1e-3);
+
+    assert!(world.inflate(id, 2.0).unwrap());
+    assert_eq!(world.pressure(id).unwrap().rest_area, rest_area * 2.0);
+
+    world.remove_polygon(id).unwrap();
+    assert!(world.pressure(id).is_none());
+}
+
+#[test]
+fn inflating_without_pressure_is_reported() {
+    let mut world = World::new();
+    let id = world.add_polygon(square(40.0));
+    assert!(!world.inflate(id, 2.0).unwrap());
+    assert!(world.pressure(id).is_none());
+}
+
+#[test]
+fn a_model_replaces_the_built_in_pressure() {
+    let mut world = World::new();
+    let id = world.add_polygon(
+        PolygonShape::PressureCircle {
+            radius: 30.0,
+            pos: Vector2::zeros(),
+            point_count: 12,
+            fixed: false,
+            stiffness: 1000.0,
+            pressure: 50.0,
+        }
+        .build(),
+    );
+    world
+        .set_pressure_model(id, PressureModel::Constant { pressure: 10.0 })
+        .unwrap();
+    assert_eq!(world.polygon(id).unwrap().pressure, 0.0);
+}
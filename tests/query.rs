@@ -0,0 +1,155 @@
+use bendy2d::solver::Solver;
+use bendy2d_testing::broad_phase::Aabb;
+use bendy2d_testing::pick::BodyRef;
+use bendy2d_testing::query::SolverQuery;
+use bendy2d_testing::scene::{CircleDesc, PolygonShape};
+use nalgebra::Vector2;
+
+/// A circle of radius 10 at (50, 0) in front of a wall at x = 100.
+fn solver() -> Solver {
+    let mut solver = Solver::new();
+    solver.add_circle(
+        CircleDesc {
+            pos: Vector2::new(50.0, 0.0),
+            prev_pos: None,
+            radius: 10.0,
+        }
+        .circle(),
+    );
+    solver.add_static_line((Vector2::new(100.0, -50.0), Vector2::new(100.0, 50.0)));
+    solver
+}
+
+#[test]
+fn raycast_hits_nearest_body_first() {
+    let solver = solver();
+    let hits = solver.raycast_all(Vector2::zeros(), Vector2::x(), 200.0);
+    assert_eq!(hits.len(), 2);
+
+    assert_eq!(hits[0].body, BodyRef::Circle { index: 0 });
+    assert!((hits[0].point - Vector2::new(40.0, 0.0)).magnitude() < 1e-4);
+    assert!((hits[0].normal - Vector2::new(-1.0, 0.0)).magnitude() < 1e-4);
+
+    assert_eq!(hits[1].body, BodyRef::StaticLine { index: 0 });
+    assert!((hits[1].distance - 100.0).abs() < 1e-4);
+    assert!((hits[1].normal - Vector2::new(-1.0, 0.0)).magnitude() < 1e-4);
+
+    assert!(solver
+        .raycast(Vector2::zeros(), Vector2::x(), 30.0)
+        .is_none());
+}
+
+#[test]
+fn point_and_area_queries() {
+    let solver = solver();
+    assert_eq!(
+        solver.bodies_at(Vector2::new(55.0, 0.0)),
+        vec![BodyRef::Circle { index: 0 }]
+    );
+    assert!(solver.bodies_at(Vector2::new(70.0, 0.0)).is_empty());
+
+    let aabb = Aabb {
+        min: Vector2::new(90.0, -10.0),
+        max: Vector2::new(110.0, 10.0),
+    };
+    assert_eq!(
+        solver.query_aabb(&aabb),
+        vec![BodyRef::StaticLine { index: 0 }]
+    );
+
+    let triangle = [
+        Vector2::new(30.0, -20.0),
+        Vector2::new(55.0, 20.0),
+        Vector2::new(30.0, 20.0),
+    ];
+    assert_eq!(
+        solver.query_shape(&triangle),
+        vec![BodyRef::Circle { index: 0 }]
+    );
+}
+
+fn points(points: &[(f32, f32)]) -> Vec<Vector2<f32>> {
+    points.iter().map(|(x, y)| Vector2::new(*x, *y)).collect()
+}
+
+/// A concave L, a 30 by 10 foot with a 10 wide stem up to y = 40, and a
+/// 20 by 20 box at (100, 0).
+fn polygons() -> Solver {
+    let mut solver = Solver::new();
+    let l_shape = points(&[
+        (0.0, 0.0),
+        (30.0, 0.0),
+        (30.0, 10.0),
+        (10.0, 10.0),
+        (10.0, 40.0),
+        (0.0, 40.0),
+    ]);
+    solver.add_polygon(
+        PolygonShape::Outline {
+            points: l_shape,
+            fixed: true,
+            stiffness: 1000.0,
+            permanence_threshold: -1.0,
+        }
+        .build(),
+    );
+    solver.add_polygon(
+        PolygonShape::Box {
+            pos: Vector2::new(100.0, 0.0),
+            rotation: 0.0,
+            size: Vector2::new(20.0, 20.0),
+            mass: 1.0,
+            stiffness: 1000.0,
+            fixed: true,
+            permanence_threshold: -1.0,
+        }
+        .build(),
+    );
+    solver
+}
+
+#[test]
+fn point_queries_on_polygons() {
+    let solver = polygons();
+    let l_shape = vec![BodyRef::Polygon { index: 0 }];
+    assert_eq!(solver.bodies_at(Vector2::new(5.0, 30.0)), l_shape);
+    assert_eq!(solver.bodies_at(Vector2::new(25.0, 5.0)), l_shape);
+    // Inside the L's bounds but in the notch between foot and stem
+    assert!(solver.bodies_at(Vector2::new(20.0, 25.0)).is_empty());
+    assert_eq!(
+        solver.bodies_at(Vector2::new(105.0, 5.0)),
+        vec![BodyRef::Polygon { index: 1 }]
+    );
+    assert!(solver.bodies_at(Vector2::new(60.0, 5.0)).is_empty());
+}
+
+#[test]
+fn area_queries_on_polygons() {
+    let solver = polygons();
+    let l_shape = vec![BodyRef::Polygon { index: 0 }];
+
+    // Boxes are only tested against the polygons' bounds, notch included
+    let notch = Aabb {
+        min: Vector2::new(15.0, 20.0),
+        max: Vector2::new(25.0, 30.0),
+    };
+    assert_eq!(solver.query_aabb(&notch), l_shape);
+    let between = Aabb {
+        min: Vector2::new(40.0, 0.0),
+        max: Vector2::new(60.0, 10.0),
+    };
+    assert!(solver.query_aabb(&between).is_empty());
+
+    // Shapes follow the outline
+    let in_notch = points(&[(15.0, 20.0), (25.0, 20.0), (20.0, 30.0)]);
+    assert!(solver.query_shape(&in_notch).is_empty());
+    let across_stem = points(&[(5.0, 20.0), (20.0, 20.0), (20.0, 30.0)]);
+    assert_eq!(solver.query_shape(&across_stem), l_shape);
+    let inside_stem = points(&[(2.0, 20.0), (8.0, 20.0), (5.0, 30.0)]);
+    assert_eq!(solver.query_shape(&inside_stem), l_shape);
+    let around_all = points(&[(-10.0, -20.0), (130.0, -20.0), (130.0, 50.0), (-10.0, 50.0)]);
+    assert_eq!(
+        solver.query_shape(&around_all),
+        vec![BodyRef::Polygon { index: 0 }, BodyRef::Polygon { index: 1 }]
+    );
+}
@@ -0,0 +1,111 @@
+use bendy2d::link::ParticleLink;
+use bendy2d::particle::Particle;
+use bendy2d::solver::Solver;
+use bendy2d_testing::pick::BodyRef;
+use bendy2d_testing::remove::SolverRemove;
+use bendy2d_testing::scene::{LinkDesc, SpringDesc};
+use bendy2d_testing::snapshot::Snapshot;
+use bendy2d_testing::world::World;
+use nalgebra::Vector2;
+
+fn spring(particle_a: usize, particle_b: usize) -> SpringDesc {
+    SpringDesc {
+        particle_a,
+        particle_b,
+        rest_length: 10.0,
+        stiffness: 100.0,
+        permanence_threshold: -1.0,
+    }
+}
+
+/// Four particles in a row, joined by springs and a link.
+fn snapshot() -> Snapshot {
+    Snapshot {
+        gravity: Vector2::zeros(),
+        bounds_pos: Vector2::new(-100.0, -100.0),
+        bounds_size: Vector2::new(200.0, 200.0),
+        polygons: Vec::new(),
+        particles: (0..4)
+            .map(|i| Particle::new(Vector2::new(i as f32 * 10.0, 0.0)))
+            .collect(),
+        springs: vec![spring(0, 1), spring(2, 3), spring(0, 2)],
+        particle_links: vec![LinkDesc {
+            particle_a: 3,
+            particle_b: 0,
+            target_distance: 30.0,
+        }],
+        circles: Vec::new(),
+        circle_links: Vec::new(),
+        static_lines: Vec::new(),
+    }
+}
+
+#[test]
+fn removing_a_particle_moves_the_last_into_its_place() {
+    let mut snapshot = snapshot();
+    assert!(snapshot.remove(BodyRef::Particle { index: 1 }));
+
+    let xs: Vec<f32> = snapshot.particles.iter().map(|p| p.pos.x).collect();
+    assert_eq!(xs, [0.0, 30.0, 20.0]);
+    // The spring on the removed particle goes, the ones on the last follow it
+    let ends: Vec<_> = snapshot
+        .springs
+        .iter()
+        .map(|spring| (spring.particle_a, spring.particle_b))
+        .collect();
+    assert_eq!(ends, [(2, 1), (0, 2)]);
+    let link = &snapshot.particle_links[0];
+    assert_eq!((link.particle_a, link.particle_b), (1, 0));
+}
+
+#[test]
+fn removing_the_last_particle_leaves_the_rest_in_place() {
+    let mut snapshot = snapshot();
+    assert!(snapshot.remove(BodyRef::Particle { index: 3 }));
+    assert_eq!(snapshot.particles.len(), 3);
+    assert_eq!(snapshot.springs.len(), 2);
+    assert!(snapshot.particle_links.is_empty());
+    assert!(!snapshot.remove(BodyRef::Particle { index: 3 }));
+}
+
+#[test]
+fn handles_follow_a_swap_removal() {
+    let mut world = World::new();
+    let ids: Vec<_> = (0..4)
+        .map(|i| world.add_particle(Vector2::new(i as f32 * 10.0, 0.0)))
+        .collect();
+    world.remove_particle(ids[1]).unwrap();
+
+    assert_eq!(world.particle_index(ids[3]), Ok(1));
+    for (id, x) in [(ids[0], 0.0), (ids[2], 20.0), (ids[3], 30.0)] {
+        assert_eq!(world.particle(id).unwrap().pos.x, x);
+    }
+}
+
+#[test]
+fn solver_removal_reindexes_in_place() {
+    let mut solver = Solver::new();
+    let snapshot = snapshot();
+    for particle in snapshot.particles.iter() {
+        solver.add_particle(particle.pos);
+    }
+    for spring in snapshot.springs.iter() {
+        solver.add_particle_spring(spring.spring());
+    }
+    for link in snapshot.particle_links.iter() {
+        solver.add_particle_link(ParticleLink { link: link.link() });
+    }
+    assert!(solver.remove(BodyRef::Particle { index: 1 }));
+
+    let xs: Vec<f32> = solver.get_particles().iter().map(|p| p.pos.x).collect();
+    assert_eq!(xs, [0.0, 30.0, 20.0]);
+    let ends: Vec<_> = solver
+        .get_particle_springs()
+        .iter()
+        .map(|spring| (spring.particle_a, spring.particle_b))
+        .collect();
+    assert_eq!(ends, [(2, 1), (0, 2)]);
+    let link = &solver.get_particle_links()[0].link;
+    assert_eq!((link.particle_a, link.particle_b), (1, 0));
+    assert!(!solver.remove(BodyRef::Polygon { index: 0 }));
+}
@@ -0,0 +1,138 @@
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::broad_phase::BroadPhase;
+use bendy2d_testing::joint::{Anchor, JointDesc, JointKind};
+use bendy2d_testing::kinematic::Motion;
+use bendy2d_testing::material::Material;
+use bendy2d_testing::pick::{BodyRef, PointRef};
+use bendy2d_testing::replay::{self, Action, Recording};
+use bendy2d_testing::scene::{PolygonShape, Scene};
+use bendy2d_testing::world::{Timestep, World};
+use nalgebra::Vector2;
+
+fn shape(x: f32, y: f32) -> PolygonShape {
+    PolygonShape::Box {
+        pos: Vector2::new(x, y),
+        rotation: 0.0,
+        size: Vector2::new(40.0, 40.0),
+        mass: 1.0,
+        stiffness: 1000.0,
+        fixed: false,
+        permanence_threshold: -1.0,
+    }
+}
+
+/// A world with every option changed from its default and something
+/// attached to its bodies, as it might be when recording starts.
+fn world() -> (World, Vec<PolygonShape>) {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::new(0.0, 980.0);
+    solver.bounds = Bounds {
+        pos: Vector2::new(-500.0, -500.0),
+        size: Vector2::new(1000.0, 1000.0),
+    };
+    let mut world = World::from_solver(solver);
+    let shapes = vec![shape(0.0, 0.0), shape(45.0, 0.0), shape(0.0, 200.0)];
+    let ids: Vec<_> = shapes
+        .iter()
+        .map(|shape| world.add_polygon(shape.build()))
+        .collect();
+
+    world.set_swept_collisions(true);
+    world.set_broad_phase(BroadPhase::Grid);
+    world.set_swept_threads(2);
+    world.set_break_strain(Some(0.5));
+    world.set_timestep(Timestep {
+        step: 0.005,
+        substeps: 2,
+        max_steps: 3,
+    });
+    world.set_material(
+        BodyRef::Polygon { index: 2 },
+        Material {
+            static_friction: 0.5,
+            dynamic_friction: 0.3,
+            restitution: 0.8,
+        },
+    );
+    world
+        .add_joint(&JointDesc {
+            a: Anchor::Polygon {
+                polygon: ids[0],
+                pos: Vector2::new(22.5, 0.0),
+            },
+            b: Anchor::Polygon {
+                polygon: ids[1],
+                pos: Vector2::new(22.5, 0.0),
+            },
+            kind: JointKind::Weld,
+        })
+        .unwrap();
+    world
+        .set_kinematic(
+            ids[2],
+            Motion::Spin {
+                angular_velocity: 1.0,
+            },
+        )
+        .unwrap();
+    (world, shapes)
+}
+
+#[test]
+fn replay_rebuilds_the_recorded_world() {
+    let (mut world, shapes) = world();
+    // Run a while first so recording starts from a moving world rather
+    // than the one it was built as
+    for _ in 0..20 {
+        world.step();
+    }
+    let mut recording = Recording::new(&world, Scene::from_world(&world, &shapes).unwrap());
+
+    let script = [
+        (
+            3,
+            Action::Throw {
+                point: PointRef::PolygonVertex {
+                    polygon: 0,
+                    vertex: 0,
+                },
+                velocity: Vector2::new(2.0, -3.0),
+            },
+        ),
+        (8, Action::AddPolygon(shape(-100.0, 0.0))),
+        (30, Action::SetSweptCollisions { enabled: false }),
+    ];
+    let last = script.last().unwrap().0;
+    for frame in 0..=last {
+        for (_, action) in script.iter().filter(|(at, _)| *at == frame) {
+            action.apply(&mut world).unwrap();
+            recording.record(frame, action.clone());
+        }
+        if frame < last {
+            world.step();
+        }
+    }
+
+    let replayed = replay::replay(recording).unwrap();
+    assert_eq!(replayed.timestep(), world.timestep());
+    assert_eq!(replayed.swept_collisions(), world.swept_collisions());
+    assert_eq!(replayed.broad_phase(), world.broad_phase());
+    assert_eq!(replayed.swept_threads(), world.swept_threads());
+    assert_eq!(replayed.break_strain(), world.break_strain());
+    assert_eq!(replayed.joints().count(), 1);
+    for index in 0..world.get_polygons_len() {
+        let body = BodyRef::Polygon { index };
+        assert_eq!(replayed.material(body), world.material(body));
+        let id = world.polygon_id(index).unwrap();
+        assert_eq!(replayed.motion(id), world.motion(id));
+        let points = |world: &World| -> Vec<_> {
+            let polygon = world.get_polygon(index).unwrap();
+            polygon
+                .particles
+                .iter()
+                .map(|p| (p.pos, p.prev_pos))
+                .collect()
+        };
+        assert_eq!(points(&replayed), points(&world), "polygon {}", index);
+    }
+}
@@ -0,0 +1,341 @@
+use bendy2d::circle::Circle;
+use bendy2d::particle::Particle;
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::connector::{Connector, ConnectorKind, Endpoint};
+use bendy2d_testing::filter::CollisionFilter;
+use bendy2d_testing::joint::{Anchor, JointDesc, JointKind};
+use bendy2d_testing::kinematic::{Motion, Transform};
+use bendy2d_testing::material::{Combine, CombineRules, Material};
+use bendy2d_testing::pick::BodyRef;
+use bendy2d_testing::pressure::{Pressure, PressureModel};
+use bendy2d_testing::scene::{LinkDesc, PolygonShape, Scene};
+use bendy2d_testing::world::{CircleLink, ParticleLink, PolygonId, Spring, World};
+use nalgebra::Vector2;
+
+fn shape(x: f32) -> PolygonShape {
+    PolygonShape::Box {
+        pos: Vector2::new(x, 0.0),
+        rotation: 0.0,
+        size: Vector2::new(40.0, 40.0),
+        mass: 1.0,
+        stiffness: 1000.0,
+        fixed: false,
+        permanence_threshold: -1.0,
+    }
+}
+
+/// Three boxes with the first removed again, so the ids of the other two
+/// no longer follow list order.
+fn world() -> (World, Vec<PolygonShape>, [PolygonId; 2]) {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::new(0.0, 980.0);
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    let shapes: Vec<_> = [-100.0, 0.0, 100.0].into_iter().map(shape).collect();
+    let ids: Vec<_> = shapes
+        .iter()
+        .map(|shape| world.add_polygon(shape.build()))
+        .collect();
+    world.remove_polygon(ids[0]).unwrap();
+    // The last box moves into the removed one's place
+    let mut shapes = shapes;
+    shapes.swap_remove(0);
+    (world, shapes, [ids[1], ids[2]])
+}
+
+/// Saves the world as a scene and loads it into a fresh world, through
+/// JSON like a scene file.
+fn reload(world: &World, shapes: &[PolygonShape]) -> World {
+    let scene = Scene::from_world(world, shapes).unwrap();
+    let scene: Scene = serde_json::from_str(&serde_json::to_string(&scene).unwrap()).unwrap();
+    scene.check().unwrap();
+    let mut loaded = World::new();
+    loaded.load_scene(&scene);
+    loaded
+}
+
+#[test]
+fn bodies_keep_their_ids() {
+    let (world, shapes, ids) = world();
+    let loaded = reload(&world, &shapes);
+    for id in ids {
+        assert_eq!(loaded.polygon_index(id), world.polygon_index(id));
+    }
+}
+
+#[test]
+fn joints_and_connectors_are_saved() {
+    let (mut world, shapes, [a, b]) = world();
+    let joint = world
+        .add_joint(&JointDesc {
+            a: Anchor::Polygon {
+                polygon: a,
+                pos: Vector2::new(50.0, 0.0),
+            },
+            b: Anchor::Polygon {
+                polygon: b,
+                pos: Vector2::new(50.0, 0.0),
+            },
+            kind: JointKind::Revolute { limits: None },
+        })
+        .unwrap();
+    let connector = world
+        .add_connector(&Connector {
+            a: Endpoint::PolygonVertex {
+                polygon: b,
+                vertex: 0,
+            },
+            b: Endpoint::World {
+                pos: Vector2::new(100.0, -100.0),
+            },
+            kind: ConnectorKind::Link {
+                target_distance: 80.0,
+            },
+        })
+        .unwrap();
+    let mut loaded = reload(&world, &shapes);
+
+    for _ in 0..100 {
+        world.update(0.01);
+        loaded.update(0.01);
+    }
+    let anchors = |world: &World| world.joint_anchors(world.joint(joint).unwrap());
+    assert_eq!(anchors(&loaded), anchors(&world));
+    let ends = |world: &World| {
+        let (_, connector) = world.connectors().find(|(id, _)| *id == connector)?;
+        world.connector_ends(connector)
+    };
+    assert!(ends(&loaded).is_some());
+    assert_eq!(ends(&loaded), ends(&world));
+}
+
+#[test]
+fn materials_are_saved() {
+    let (mut world, shapes, [_, b]) = world();
+    world.add_static_line((Vector2::new(-200.0, 100.0), Vector2::new(200.0, 100.0)));
+    let bouncy = Material {
+        static_friction: 0.2,
+        dynamic_friction: 0.1,
+        restitution: 0.9,
+    };
+    let rough = Material {
+        static_friction: 1.0,
+        dynamic_friction: 0.8,
+        restitution: 0.0,
+    };
+    let polygon = BodyRef::Polygon {
+        index: world.polygon_index(b).unwrap(),
+    };
+    let line = BodyRef::StaticLine { index: 0 };
+    world.set_material(polygon, bouncy);
+    world.set_material(line, rough);
+    world.set_default_material(rough);
+    world.set_combine_rules(CombineRules {
+        friction: Combine::Max,
+        restitution: Combine::Min,
+    });
+
+    let loaded = reload(&world, &shapes);
+    assert_eq!(loaded.material(polygon), Some(bouncy));
+    assert_eq!(loaded.material(line), Some(rough));
+    assert_eq!(loaded.default_material(), rough);
+    assert_eq!(loaded.combine_rules(), world.combine_rules());
+}
+
+#[test]
+fn collision_filters_are_saved() {
+    let (mut world, shapes, [a, b]) = world();
+    let filter = CollisionFilter {
+        layers: 2,
+        mask: 6,
+        group: 3,
+    };
+    world.set_polygon_filter(b, filter).unwrap();
+
+    let loaded = reload(&world, &shapes);
+    let body = |id| BodyRef::Polygon {
+        index: loaded.polygon_index(id).unwrap(),
+    };
+    assert_eq!(loaded.collision_filter(body(b)), Some(filter));
+    assert_eq!(
+        loaded.collision_filter(body(a)),
+        Some(CollisionFilter::default())
+    );
+}
+
+#[test]
+fn kinematic_motions_are_saved() {
+    let (mut world, shapes, [a, b]) = world();
+    let spin = Motion::Spin {
+        angular_velocity: 2.0,
+    };
+    world.set_kinematic(a, spin).unwrap();
+    let transform = Transform {
+        translation: Vector2::new(5.0, 0.0),
+        rotation: 0.0,
+    };
+    world.set_kinematic_transform(b, transform).unwrap();
+    for _ in 0..10 {
+        world.update(0.01);
+    }
+
+    let mut loaded = reload(&world, &shapes);
+    assert_eq!(loaded.motion(a), Some(spin));
+    assert_eq!(loaded.motion(b), Some(Motion::Manual(transform)));
+    // Carries on from the same time and placement
+    for _ in 0..10 {
+        world.update(0.01);
+        loaded.update(0.01);
+    }
+    for id in [a, b] {
+        let pos = |world: &World| world.polygon(id).unwrap().particles[0].pos;
+        assert_eq!(pos(&loaded), pos(&world));
+    }
+}
+
+#[test]
+fn shape_matching_keeps_its_rest_shape() {
+    let (mut world, shapes, [a, _]) = world();
+    world.set_shape_match(a, 0.5).unwrap();
+    // Dent the box after its rest shape was taken
+    let index = world.polygon_index(a).unwrap();
+    world.solver_mut().get_polygon_mut(index).unwrap().particles[0].pos += Vector2::new(10.0, 10.0);
+
+    let mut loaded = reload(&world, &shapes);
+    assert_eq!(loaded.shape_match(a), Some(0.5));
+    world.update(0.01);
+    loaded.update(0.01);
+    let points = |world: &World| -> Vec<_> {
+        let polygon = world.polygon(a).unwrap();
+        polygon.particles.iter().map(|p| p.pos).collect()
+    };
+    assert_eq!(points(&loaded), points(&world));
+}
+
+#[test]
+fn pressure_is_saved() {
+    let (mut world, shapes, [a, _]) = world();
+    let pressure = Pressure {
+        model: PressureModel::IdealGas { pressure: 5.0 },
+        rest_area: 2000.0,
+    };
+    world.set_pressure(a, Some(pressure)).unwrap();
+
+    let loaded = reload(&world, &shapes);
+    assert_eq!(loaded.pressure(a), Some(pressure));
+}
+
+/// Every position in the solver, in list order.
+fn positions(solver: &Solver) -> Vec<(Vector2<f32>, Vector2<f32>)> {
+    let pair = |p: &Particle| (p.pos, p.prev_pos);
+    let polygons = solver
+        .get_polygons()
+        .iter()
+        .flat_map(|p| p.particles.iter());
+    let circles = solver.get_circles().iter().map(|c| &c.point);
+    polygons
+        .chain(solver.get_particles().iter())
+        .chain(circles)
+        .map(pair)
+        .collect()
+}
+
+#[test]
+fn save_load_build_round_trip() {
+    let (mut world, shapes, _) = world();
+    let a = world.add_particle(Vector2::new(0.0, -100.0));
+    let b = world.add_particle(Vector2::new(30.0, -100.0));
+    world
+        .add_particle_spring(&Spring {
+            particle_a: a,
+            particle_b: b,
+            rest_length: 30.0,
+            stiffness: 100.0,
+            permanence_threshold: -1.0,
+        })
+        .unwrap();
+    world
+        .add_particle_link(&ParticleLink {
+            particle_a: a,
+            particle_b: b,
+            target_distance: 30.0,
+        })
+        .unwrap();
+    let circle = |x: f32| Circle {
+        point: Particle::new(Vector2::new(x, -200.0)),
+        radius: 10.0,
+    };
+    let c = world.add_circle(circle(0.0));
+    let d = world.add_circle(circle(40.0));
+    world
+        .add_circle_link(&CircleLink {
+            circle_a: c,
+            circle_b: d,
+            target_distance: 40.0,
+        })
+        .unwrap();
+    world.add_static_line((Vector2::new(-200.0, 100.0), Vector2::new(200.0, 100.0)));
+    // Get everything moving
+    for _ in 0..20 {
+        world.update(0.01);
+    }
+
+    let path = std::env::temp_dir().join("bendy2d_testing_round_trip.json");
+    let path = path.to_str().unwrap();
+    Scene::from_world(&world, &shapes)
+        .unwrap()
+        .save(path)
+        .unwrap();
+    let solver = Scene::load(path).unwrap().build();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(positions(&solver), positions(&world));
+    assert_eq!(solver.get_particle_springs().len(), 1);
+    assert_eq!(solver.get_particle_links().len(), 1);
+    assert_eq!(solver.get_circle_links().len(), 1);
+    assert_eq!(solver.get_static_lines(), world.get_static_lines());
+}
+
+/// The scene of `world()` with one free particle and a spring from it to
+/// itself, as a base for broken scenes.
+fn valid_scene() -> Scene {
+    let (mut world, shapes, _) = world();
+    let a = world.add_particle(Vector2::zeros());
+    world
+        .add_particle_spring(&Spring {
+            particle_a: a,
+            particle_b: a,
+            rest_length: 0.0,
+            stiffness: 1.0,
+            permanence_threshold: -1.0,
+        })
+        .unwrap();
+    let scene = Scene::from_world(&world, &shapes).unwrap();
+    scene.check().unwrap();
+    scene
+}
+
+#[test]
+fn out_of_range_indices_are_rejected() {
+    let mut scene = valid_scene();
+    scene.springs[0].particle_b = 1;
+    assert!(scene.check().is_err());
+
+    let mut scene = valid_scene();
+    scene.circle_links.push(LinkDesc {
+        particle_a: 0,
+        particle_b: 0,
+        target_distance: 1.0,
+    });
+    assert!(scene.check().is_err());
+}
+
+#[test]
+fn saved_particle_counts_must_match_the_shape() {
+    let mut scene = valid_scene();
+    scene.polygons[0].particles.pop();
+    assert!(scene.check().is_err());
+}
@@ -0,0 +1,79 @@
+use bendy2d::polygon::Polygon;
+use bendy2d::solver::{Bounds, Solver};
+use bendy2d_testing::outline::signed_area;
+use bendy2d_testing::scene::PolygonShape;
+use bendy2d_testing::shape_match::ShapeMatch;
+use bendy2d_testing::world::World;
+use nalgebra::Vector2;
+
+fn square() -> Polygon {
+    PolygonShape::Box {
+        pos: Vector2::zeros(),
+        rotation: 0.0,
+        size: Vector2::new(40.0, 40.0),
+        mass: 1.0,
+        stiffness: 1000.0,
+        fixed: false,
+        permanence_threshold: -1.0,
+    }
+    .build()
+}
+
+fn area(polygon: &Polygon) -> f32 {
+    let points: Vec<Vector2<f32>> = polygon.particles.iter().map(|p| p.pos).collect();
+    signed_area(&points)
+}
+
+#[test]
+fn rigid_match_undoes_an_inversion() {
+    let mut polygon = square();
+    let rest_area = area(&polygon);
+    let shape_match = ShapeMatch::new(&polygon, 1.0);
+
+    // Mirror the square, turning it inside out
+    for particle in polygon.particles.iter_mut() {
+        particle.pos.x = -particle.pos.x;
+    }
+    assert!(area(&polygon) * rest_area < 0.0);
+
+    shape_match.solve(&mut polygon);
+    assert!((area(&polygon) - rest_area).abs() < 1e-2 * rest_area.abs());
+}
+
+#[test]
+fn crushed_polygon_recovers() {
+    let mut solver = Solver::new();
+    solver.gravity = Vector2::zeros();
+    solver.bounds = Bounds {
+        pos: Vector2::new(-10_000.0, -10_000.0),
+        size: Vector2::new(20_000.0, 20_000.0),
+    };
+    let mut world = World::from_solver(solver);
+    let id = world.add_polygon(square());
+    let rest_area = area(world.polygon(id).unwrap());
+    world.set_shape_match(id, 0.2).unwrap();
+
+    // Squash the square flat
+    let index = world.polygon_index(id).unwrap();
+    for particle in world
+        .solver_mut()
+        .get_polygon_mut(index)
+        .unwrap()
+        .particles
+        .iter_mut()
+    {
+        particle.pos.y *= 0.1;
+        particle.prev_pos = particle.pos;
+    }
+
+    for _ in 0..300 {
+        world.update(0.01);
+    }
+    let recovered = area(world.polygon(id).unwrap());
+    assert!(
+        (recovered - rest_area).abs() < 0.1 * rest_area.abs(),
+        "area {} after recovering, {} at rest",
+        recovered,
+        rest_area
+    );
+}
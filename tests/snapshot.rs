@@ -0,0 +1,76 @@
+use bendy2d::particle::Particle;
+use bendy2d::solver::Solver;
+use bendy2d_testing::scenarios;
+use bendy2d_testing::snapshot::SolverSnapshot;
+use nalgebra::Vector2;
+
+/// The bits of every position and previous position in the solver, so two
+/// states only compare equal if they are exactly the same.
+fn bits(solver: &Solver) -> Vec<u32> {
+    let particles = solver
+        .get_particles()
+        .iter()
+        .chain(
+            solver
+                .get_polygons()
+                .iter()
+                .flat_map(|p| p.particles.iter()),
+        )
+        .chain(solver.get_circles().iter().map(|circle| &circle.point));
+    particles
+        .flat_map(|particle: &Particle| {
+            [particle.pos, particle.prev_pos]
+                .into_iter()
+                .flat_map(|v| [v.x.to_bits(), v.y.to_bits()])
+        })
+        .collect()
+}
+
+#[test]
+fn restoring_a_snapshot_replays_bit_for_bit() {
+    let mut scenario = scenarios::deterministic();
+    let dt = scenario.dt;
+    let solver = &mut scenario.solver;
+    for _ in 0..100 {
+        solver.update(dt);
+    }
+    let snapshot = solver.snapshot();
+    let at_snapshot = bits(solver);
+
+    for _ in 0..100 {
+        solver.update(dt);
+    }
+    let first = bits(solver);
+    assert_ne!(first, at_snapshot);
+
+    solver.restore(&snapshot);
+    assert_eq!(bits(solver), at_snapshot);
+    for _ in 0..100 {
+        solver.update(dt);
+    }
+    assert_eq!(bits(solver), first);
+}
+
+#[test]
+fn restoring_drops_bodies_added_since() {
+    let mut scenario = scenarios::deterministic();
+    let solver = &mut scenario.solver;
+    let snapshot = solver.snapshot();
+    let at_snapshot = bits(solver);
+    let lens = |solver: &Solver| {
+        (
+            solver.get_particle_len(),
+            solver.get_circles_len(),
+            solver.get_polygons_len(),
+            solver.get_particle_springs().len(),
+            solver.get_static_lines().len(),
+        )
+    };
+    let before = lens(solver);
+
+    solver.add_particle(Vector2::new(1.0, 2.0));
+    solver.add_static_line((Vector2::zeros(), Vector2::new(10.0, 0.0)));
+    solver.restore(&snapshot);
+    assert_eq!(lens(solver), before);
+    assert_eq!(bits(solver), at_snapshot);
+}
@@ -0,0 +1,30 @@
+use bendy2d_testing::runner;
+use bendy2d_testing::scenarios;
+use bendy2d_testing::world::World;
+
+/// Steps the deterministic scenario with swept collisions on `threads`
+/// threads and returns the polygon positions after every step.
+fn run(threads: usize) -> Vec<Vec<nalgebra::Vector2<f32>>> {
+    let scenario = scenarios::deterministic();
+    let mut world = World::from_solver(scenario.solver);
+    world.set_swept_collisions(true);
+    world.set_swept_threads(threads);
+    (0..scenario.steps)
+        .map(|_| {
+            world.update(scenario.dt);
+            runner::polygon_positions(&world)
+        })
+        .collect()
+}
+
+#[test]
+fn thread_count_does_not_change_results() {
+    let single = run(1);
+    for threads in [2, 4] {
+        assert!(
+            run(threads) == single,
+            "{} threads diverged from 1 thread",
+            threads
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use bendy2d_testing::world::{Timestep, World};
+use nalgebra::Vector2;
+
+#[test]
+fn throws_move_per_update() {
+    let timestep = Timestep {
+        step: 0.01,
+        substeps: 2,
+        max_steps: 5,
+    };
+    // 40 over a 0.02 s frame is 2 steps of 2 updates, 10 per update
+    let velocity = timestep.per_update(Vector2::new(40.0, -20.0), 0.02);
+    assert!((velocity - Vector2::new(10.0, -5.0)).magnitude() < 1e-4);
+    // A frame shorter than an update doesn't speed the throw up
+    let velocity = timestep.per_update(Vector2::new(1.0, 0.0), 0.001);
+    assert_eq!(velocity, Vector2::new(1.0, 0.0));
+}
+
+fn world(max_steps: u32) -> World {
+    let mut world = World::new();
+    world.set_timestep(Timestep {
+        step: 0.01,
+        substeps: 2,
+        max_steps,
+    });
+    world
+}
+
+#[test]
+fn frame_time_carries_over_to_the_next_step() {
+    let mut world = world(5);
+    assert_eq!(world.advance(0.025), 2);
+    assert!((world.alpha() - 0.5).abs() < 1e-3);
+    // The leftover half step and this three quarters make one more
+    assert_eq!(world.accumulate(0.0075), 1);
+    assert!((world.alpha() - 0.25).abs() < 1e-3);
+}
+
+#[test]
+fn steps_past_the_most_per_frame_are_dropped() {
+    let mut world = world(5);
+    assert_eq!(world.accumulate(0.105), 5);
+    assert!((world.alpha() - 0.5).abs() < 1e-3);
+    // The five steps left over don't run on the next frame
+    assert_eq!(world.accumulate(0.0), 0);
+}
+
+#[test]
+fn only_positive_finite_steps_are_valid() {
+    let with_step = |step| Timestep {
+        step,
+        ..Timestep::default()
+    };
+    assert!(with_step(0.01).is_valid());
+    for step in [0.0, -0.01, f32::NAN, f32::INFINITY] {
+        assert!(!with_step(step).is_valid(), "step {}", step);
+    }
+}
+
+#[test]
+#[should_panic(expected = "positive and finite")]
+fn zero_step_is_rejected() {
+    let mut world = World::new();
+    world.set_timestep(Timestep {
+        step: 0.0,
+        ..Timestep::default()
+    });
+}
+
+#[test]
+#[should_panic(expected = "positive and finite")]
+fn nan_step_is_rejected() {
+    let mut world = World::new();
+    world.set_timestep(Timestep {
+        step: f32::NAN,
+        ..Timestep::default()
+    });
+}